@@ -0,0 +1,207 @@
+//! Benchmarking a warrior against a fixed set of reference warriors.
+//!
+//! The candidate fights each reference warrior in turn, always loaded first, and its overall
+//! score is its average score against them, so that scores from the same benchmark set can be
+//! compared over time.
+use crate::{
+    core::CoreBuilder,
+    error::CoreError,
+    runner::Runner,
+    score::{RoundResult, Scoring},
+    warrior::Warrior,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Runs a candidate warrior against a benchmark set.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    rounds: usize,
+    runner: Runner,
+}
+
+impl Default for Benchmark {
+    fn default() -> Self {
+        Self {
+            rounds: 100,
+            runner: Runner::new(),
+        }
+    }
+}
+
+impl Benchmark {
+    /// Creates a new benchmark, in which the candidate fights each opponent for 100 rounds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of rounds fought against each opponent.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds against each opponent.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Runs `candidate` against every warrior in `opponents`, using the settings in `builder` for
+    /// every round and scoring each round with `scoring`. Any warriors already loaded into
+    /// `builder` are ignored.
+    pub fn run<S: Scoring + ?Sized>(
+        &self,
+        builder: &CoreBuilder,
+        candidate: &Warrior,
+        opponents: &[Warrior],
+        scoring: &S,
+    ) -> Result<BenchReport, CoreError> {
+        let builders = opponents
+            .iter()
+            .map(|opponent| {
+                let mut builder = builder.clone();
+                builder.load_warriors(&[candidate.clone(), opponent.clone()])?;
+                Ok(builder)
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+
+        let rounds = self.runner.run_each(&builders, self.rounds)?;
+
+        let opponents: Vec<OpponentResult> = opponents
+            .iter()
+            .zip(rounds)
+            .map(|(opponent, rounds)| OpponentResult::new(opponent, &rounds, scoring))
+            .collect();
+        let score = if opponents.is_empty() {
+            0.0
+        } else {
+            opponents.iter().map(|o| o.score).sum::<f64>() / opponents.len() as f64
+        };
+
+        Ok(BenchReport {
+            candidate: candidate.metadata.display_name(),
+            rounds: self.rounds,
+            opponents,
+            score,
+        })
+    }
+}
+
+/// The candidate's results against a single opponent in a benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpponentResult {
+    /// The opponent's name and author.
+    pub name: String,
+    /// The number of rounds the candidate won.
+    pub wins: usize,
+    /// The number of rounds the candidate lost.
+    pub losses: usize,
+    /// The number of rounds that ended in a tie.
+    pub ties: usize,
+    /// The candidate's score against the opponent: the number of points it scored per 100 rounds.
+    pub score: f64,
+}
+
+impl OpponentResult {
+    fn new<S: Scoring + ?Sized>(opponent: &Warrior, rounds: &[RoundResult], scoring: &S) -> Self {
+        let mut result = Self {
+            name: opponent.metadata.display_name(),
+            wins: 0,
+            losses: 0,
+            ties: 0,
+            score: 0.0,
+        };
+
+        let mut points = 0.0;
+        for round in rounds {
+            if round.won(0) {
+                result.wins += 1;
+            } else if round.survived(0) {
+                result.ties += 1;
+            } else {
+                result.losses += 1;
+            }
+            points += scoring.points(round, 0);
+        }
+        if !rounds.is_empty() {
+            result.score = points / rounds.len() as f64 * 100.0;
+        }
+
+        result
+    }
+}
+
+/// The results of a [`Benchmark`](struct.Benchmark.html), which can be serialised to keep track
+/// of a warrior's performance over time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// The candidate's name and author.
+    pub candidate: String,
+    /// The number of rounds fought against each opponent.
+    pub rounds: usize,
+    /// The candidate's results against each opponent, in the order they were given.
+    pub opponents: Vec<OpponentResult>,
+    /// The candidate's overall score: its average score against every opponent.
+    pub score: f64,
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.candidate)?;
+        writeln!(
+            f,
+            "{:<40} {:>6} {:>6} {:>6} {:>8}",
+            "Opponent", "Wins", "Losses", "Ties", "Score"
+        )?;
+        for opponent in &self.opponents {
+            writeln!(
+                f,
+                "{:<40} {:>6} {:>6} {:>6} {:>8.1}",
+                opponent.name, opponent.wins, opponent.losses, opponent.ties, opponent.score
+            )?;
+        }
+        write!(
+            f,
+            "Overall score {:.1} against {} warriors, {} rounds each",
+            self.score,
+            self.opponents.len(),
+            self.rounds
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::Separation, score::Standard};
+
+    #[test]
+    fn imp_gate_against_imps() {
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 0).unwrap();
+        let imp_gate = Warrior::parse(include_str!("../warriors/impgate.red"), 0).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder.separation(Separation::Fixed(500));
+
+        let report = Benchmark::new()
+            .rounds(4)
+            .run(&builder, &imp_gate, &[imp.clone(), imp], &Standard::new())
+            .unwrap();
+
+        assert_eq!(report.opponents.len(), 2);
+        assert_eq!(
+            (
+                report.opponents[0].wins,
+                report.opponents[0].losses,
+                report.opponents[0].ties
+            ),
+            (4, 0, 0)
+        );
+        assert_eq!(report.opponents[0].score, 300.0);
+        assert_eq!(report.score, 300.0);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<BenchReport>(&json).unwrap(), report);
+    }
+}
@@ -0,0 +1,445 @@
+//! Guessing what kind of warrior a warrior is from how it behaves.
+//!
+//! The warrior is run on its own, so that nothing interferes with it, while a
+//! [`Profile`](struct.Profile.html) of what it does is collected: how many processes it has, how
+//! regularly it bombs, whether it copies itself, how much it scans and whether it moves like an
+//! imp. Each [`Strategy`](enum.Strategy.html) is given a score from the profile, and the warrior
+//! is labelled with the best one.
+use crate::{
+    core::{Core, CoreBuilder},
+    error::CoreError,
+    logger::{AccessKind, GameEvent, Logger},
+    parser::instruction::{Modifier, Opcode},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The broad strategies warriors follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Bombs the core at regular intervals, without looking first.
+    Stone,
+    /// Copies itself around the core, running every copy.
+    Paper,
+    /// Looks for its opponent before bombing, also called scissors.
+    Scanner,
+    /// Moves through the core by copying the instruction it is executing ahead of itself.
+    Imp,
+    /// Wipes the core cell by cell.
+    Clear,
+    /// Does more than one of the above about as much.
+    Hybrid,
+    /// Doesn't do enough of anything to tell.
+    Unknown,
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Strategy::Stone => "stone",
+                Strategy::Paper => "paper",
+                Strategy::Scanner => "scanner",
+                Strategy::Imp => "imp",
+                Strategy::Clear => "clear",
+                Strategy::Hybrid => "hybrid",
+                Strategy::Unknown => "unknown",
+            }
+        )
+    }
+}
+
+/// What a warrior did while it ran on its own.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// The number of instructions the warrior executed.
+    pub executed: usize,
+    /// The most processes the warrior had at once.
+    pub max_processes: usize,
+    /// The average number of processes the warrior had.
+    pub mean_processes: f64,
+    /// The number of cells outside its own code the warrior wrote to, other than by copying
+    /// itself.
+    pub bombs: usize,
+    /// The distance between successive bombs more than two cells apart that came up most often,
+    /// as a signed distance through the core.
+    pub stride: i64,
+    /// The fraction of successive bombs `stride` apart.
+    pub stride_regularity: f64,
+    /// The fraction of successive bombs at most two cells apart, as a clear's are.
+    pub clearing: f64,
+    /// The number of times the warrior copied one of its own instructions with `MOV.I`, other
+    /// than the one being executed or the one it copied last. Stones copy the same bomb over and
+    /// over, papers copy the whole of their code.
+    pub copies: usize,
+    /// The number of instructions the warrior executed outside the code it was loaded as.
+    pub foreign_executions: usize,
+    /// The number of comparisons the warrior made using cells it doesn't own.
+    pub scans: usize,
+    /// The number of times the warrior copied the instruction being executed elsewhere with
+    /// `MOV.I`, as imps do.
+    pub imp_moves: usize,
+}
+
+impl Profile {
+    fn fraction(&self, count: usize) -> f64 {
+        if self.executed == 0 {
+            0.0
+        } else {
+            count as f64 / self.executed as f64
+        }
+    }
+
+    /// How well the profile fits each strategy, from 0 to 1, best first.
+    pub fn scores(&self) -> Vec<(Strategy, f64)> {
+        // Most strategies spend at least a third of their instructions on what they're for, the
+        // rest being loop overhead.
+        let strength = |count: usize| (self.fraction(count) * 3.0).min(1.0);
+        let paper = strength(self.copies) * (self.fraction(self.foreign_executions) * 2.0).min(1.0);
+
+        let mut scores = vec![
+            (
+                Strategy::Stone,
+                strength(self.bombs) * self.stride_regularity,
+            ),
+            (Strategy::Paper, paper),
+            (Strategy::Scanner, strength(self.scans)),
+            (
+                Strategy::Imp,
+                (self.fraction(self.imp_moves) * 1.5).min(1.0),
+            ),
+            (Strategy::Clear, strength(self.bombs) * self.clearing),
+        ];
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+    }
+}
+
+/// A warrior's most likely strategy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Classification {
+    pub strategy: Strategy,
+    /// How sure the classification is, from 0 to 1.
+    pub confidence: f64,
+    /// The strategies a hybrid mixes, best first, or just `strategy` otherwise.
+    pub parts: Vec<Strategy>,
+    /// How well the warrior fits each strategy, from 0 to 1, best first.
+    pub scores: Vec<(Strategy, f64)>,
+    pub profile: Profile,
+}
+
+impl Classification {
+    fn from_profile(profile: Profile) -> Self {
+        let scores = profile.scores();
+        let total: f64 = scores.iter().map(|(_, score)| score).sum();
+        let (best, best_score) = scores[0];
+        let (second, second_score) = scores[1];
+
+        let (strategy, score, parts) = if best_score < 0.1 {
+            (Strategy::Unknown, 0.0, vec![])
+        } else if second_score >= 0.3 && second_score >= best_score * 0.75 {
+            (
+                Strategy::Hybrid,
+                best_score + second_score,
+                vec![best, second],
+            )
+        } else {
+            (best, best_score, vec![best])
+        };
+        // Sure of a strategy which fits well, and which stands out from the rest.
+        let confidence = if total == 0.0 {
+            0.0
+        } else {
+            (score / total) * best_score
+        };
+
+        Self {
+            strategy,
+            confidence,
+            parts,
+            scores,
+            profile,
+        }
+    }
+}
+
+impl Display for Classification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.strategy)?;
+        if self.strategy == Strategy::Hybrid {
+            let parts: Vec<String> = self.parts.iter().map(Strategy::to_string).collect();
+            write!(f, " ({})", parts.join("/"))?;
+        }
+        writeln!(f, ", {:.0}% confidence", self.confidence * 100.0)?;
+        for (strategy, score) in &self.scores {
+            writeln!(f, "  {:<8} {:.2}", strategy, score)?;
+        }
+
+        let profile = &self.profile;
+        writeln!(f)?;
+        writeln!(f, "  Instructions executed: {}", profile.executed)?;
+        writeln!(
+            f,
+            "  Processes:             {} at most, {:.1} on average",
+            profile.max_processes, profile.mean_processes
+        )?;
+        writeln!(
+            f,
+            "  Bombs:                 {}, {:.0}% {} apart, {:.0}% clearing",
+            profile.bombs,
+            profile.stride_regularity * 100.0,
+            profile.stride,
+            profile.clearing * 100.0
+        )?;
+        writeln!(f, "  Copies of itself:      {}", profile.copies)?;
+        writeln!(f, "  Executed elsewhere:    {}", profile.foreign_executions)?;
+        writeln!(f, "  Scans:                 {}", profile.scans)?;
+        write!(f, "  Imp moves:             {}", profile.imp_moves)
+    }
+}
+
+/// Classifies warriors by running them on their own.
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    cycles: usize,
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self { cycles: 20000 }
+    }
+}
+
+impl Classifier {
+    /// Creates a new classifier, which runs warriors for 20000 cycles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of cycles each warrior is run for.
+    pub fn cycles(&mut self, cycles: usize) -> &mut Self {
+        self.cycles = cycles;
+        self
+    }
+
+    /// Classifies the first warrior loaded into `builder`, running it alone with the rest of the
+    /// builder's settings.
+    pub fn classify(&self, builder: &CoreBuilder) -> Result<Classification, CoreError> {
+        let mut builder = builder.clone();
+        let warrior = builder
+            .warriors()
+            .first()
+            .cloned()
+            .ok_or(CoreError::NoWarriors)?;
+        builder
+            .cycles_before_tie(self.cycles)
+            .track_ownership(true)
+            .load_warriors(&[warrior])?;
+
+        let mut profiler = Profiler::default();
+        builder.build()?.log_with(&mut profiler).run();
+
+        Ok(Classification::from_profile(profiler.finish()))
+    }
+}
+
+/// Collects a `Profile` of the single warrior in a core.
+#[derive(Debug, Default)]
+struct Profiler {
+    profile: Profile,
+    /// The addresses the warrior was loaded at.
+    code: Option<(usize, usize)>,
+    core_size: usize,
+    last_bomb: Option<usize>,
+    last_copied: Option<usize>,
+    strides: HashMap<i64, usize>,
+    process_total: usize,
+}
+
+impl Profiler {
+    fn in_code(&self, address: usize) -> bool {
+        self.code
+            .is_some_and(|(start, len)| (address + self.core_size - start) % self.core_size < len)
+    }
+
+    fn finish(mut self) -> Profile {
+        let profile = &mut self.profile;
+        let bombs: usize = self.strides.values().sum();
+        if bombs > 0 {
+            let (mut clearing, mut longest) = (0, None);
+            for (stride, count) in &self.strides {
+                if stride.abs() <= 2 {
+                    clearing += count;
+                } else if longest.is_none_or(|(_, most)| count > most) {
+                    longest = Some((*stride, count));
+                }
+            }
+            profile.clearing = clearing as f64 / bombs as f64;
+            if let Some((stride, count)) = longest {
+                profile.stride = stride;
+                profile.stride_regularity = *count as f64 / bombs as f64;
+            }
+        }
+        if profile.executed > 0 {
+            profile.mean_processes = self.process_total as f64 / profile.executed as f64;
+        }
+
+        self.profile
+    }
+}
+
+impl Logger for Profiler {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        let execution = match event {
+            GameEvent::Executed(execution) => execution,
+            _ => return,
+        };
+        let warrior = execution.warrior.idx;
+        let instruction = &execution.instruction;
+        if self.code.is_none() {
+            self.core_size = current_game_state.instructions().len();
+            let start = current_game_state.loaded_at()[warrior];
+            self.code = Some((start, execution.warrior.len()));
+        }
+
+        let processes = current_game_state
+            .task_queues()
+            .iter()
+            .map(|(_, queue)| queue.len())
+            .sum::<usize>()
+            // The executing process has been taken off its queue, and may have died.
+            + usize::from(!execution.process_died());
+        let profile = &mut self.profile;
+        profile.executed += 1;
+        profile.max_processes = profile.max_processes.max(processes);
+        self.process_total += processes;
+        if !self.in_code(execution.pc) {
+            self.profile.foreign_executions += 1;
+        }
+
+        let copying =
+            *instruction.opcode() == Opcode::Mov && *instruction.modifier() == Modifier::I;
+        if copying && execution.a_ptr == execution.pc {
+            self.profile.imp_moves += 1;
+            return;
+        }
+        let source = &current_game_state.instructions()[execution.a_ptr];
+        if copying
+            && self.last_copied != Some(execution.a_ptr)
+            && *source.opcode() != Opcode::Dat
+            && current_game_state
+                .owner(execution.a_ptr)
+                .is_some_and(|owner| owner.warrior == warrior)
+        {
+            self.profile.copies += 1;
+            self.last_copied = Some(execution.a_ptr);
+            return;
+        }
+
+        match instruction.opcode() {
+            Opcode::Seq | Opcode::Sne | Opcode::Slt | Opcode::Jmz | Opcode::Jmn => {
+                let scanned = execution.accesses.iter().any(|access| {
+                    access.kind == AccessKind::Read
+                        && access.address != execution.pc
+                        && !self.in_code(access.address)
+                });
+                if scanned {
+                    self.profile.scans += 1;
+                }
+            }
+            _ => {}
+        }
+
+        for access in &execution.accesses {
+            if access.kind == AccessKind::Write && !self.in_code(access.address) {
+                self.profile.bombs += 1;
+                if let Some(last) = self.last_bomb {
+                    let distance = (access.address + self.core_size - last) % self.core_size;
+                    let distance = if distance > self.core_size / 2 {
+                        distance as i64 - self.core_size as i64
+                    } else {
+                        distance as i64
+                    };
+                    *self.strides.entry(distance).or_default() += 1;
+                }
+                self.last_bomb = Some(access.address);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::warrior::Warrior;
+
+    fn classify(source: &str) -> Classification {
+        let warrior = Warrior::parse(source, 0).unwrap();
+        let mut builder = CoreBuilder::new();
+        builder.load_warriors(&[warrior]).unwrap();
+        Classifier::new().classify(&builder).unwrap()
+    }
+
+    #[test]
+    fn classifies_warriors() {
+        let imp = classify(include_str!("../warriors/imp.red"));
+        assert_eq!(imp.strategy, Strategy::Imp);
+        assert!(imp.confidence > 0.9);
+
+        let dwarf = classify(include_str!("../warriors/dwarf.red"));
+        assert_eq!(dwarf.strategy, Strategy::Stone);
+        assert_eq!(dwarf.profile.stride, 4);
+
+        let paper = classify(
+            "        ORG start
+             start   MOV.AB #10, count
+                     MOV.A #-8, ptr
+             loop    MOV.I }ptr, >ptr
+                     DJN.B loop, count
+                     SUB.AB #10, ptr
+                     SPL @ptr
+                     ADD.AB #1000, ptr
+                     JMP start
+             ptr     DAT.F #0, #1000
+             count   DAT.F #0, #0
+                     END",
+        );
+        assert_eq!(paper.strategy, Strategy::Paper, "{}", paper);
+
+        let scanner = classify(
+            "        ORG scan
+             ptr     DAT #10, #20
+             scan    ADD.F step, ptr
+                     SEQ.I @ptr, *ptr
+                     JMP found
+                     JMP scan
+             found   MOV.I bomb, @ptr
+                     JMP scan
+             bomb    DAT #0, #0
+             step    DAT #20, #20
+                     END",
+        );
+        assert_eq!(scanner.strategy, Strategy::Scanner, "{}", scanner);
+
+        let clear = classify(
+            "        ORG clear
+             ptr     DAT #0, #10
+             clear   MOV.I bomb, >ptr
+                     JMP clear
+             bomb    DAT #0, #0
+                     END",
+        );
+        assert_eq!(clear.strategy, Strategy::Clear, "{}", clear);
+
+        let armadillo = classify(include_str!("../warriors/armadillo.red"));
+        assert_eq!(armadillo.strategy, Strategy::Hybrid);
+        assert_eq!(armadillo.parts.len(), 2);
+
+        let wait = classify(include_str!("../warriors/wait.red"));
+        assert_eq!(wait.strategy, Strategy::Unknown);
+    }
+}
@@ -0,0 +1,266 @@
+//! Comparing two versions of a warrior against the same opponents.
+//!
+//! Both versions fight each opponent over the same seeds, so that every round fought by the
+//! candidate has a counterpart fought by the baseline from the same starting positions. The
+//! difference in score is then measured round by round, which cancels out most of the noise
+//! caused by the positions and makes small improvements detectable in far fewer rounds.
+use crate::{
+    core::CoreBuilder,
+    error::CoreError,
+    runner::Runner,
+    score::{RoundResult, Sample, Scoring, Z_95},
+    warrior::Warrior,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Runs two versions of a warrior against a set of opponents in paired rounds.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    rounds: usize,
+    seed: Option<u64>,
+    runner: Runner,
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self {
+            rounds: 100,
+            seed: None,
+            runner: Runner::new(),
+        }
+    }
+}
+
+impl Comparison {
+    /// Creates a new comparison, in which each version fights each opponent for 100 rounds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of rounds each version fights against each opponent.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the seed the rounds' seeds are derived from, so that the comparison can be repeated
+    /// exactly. By default a random seed is used.
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Runs `baseline` and `candidate` against every warrior in `opponents`, using the settings
+    /// in `builder` for every round and scoring each round with `scoring`. Any warriors already
+    /// loaded into `builder` are ignored.
+    pub fn run<S: Scoring + ?Sized>(
+        &self,
+        builder: &CoreBuilder,
+        baseline: &Warrior,
+        candidate: &Warrior,
+        opponents: &[Warrior],
+        scoring: &S,
+    ) -> Result<ComparisonReport, CoreError> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let seeds: Vec<u64> = (0..self.rounds as u64)
+            .map(|i| seed.wrapping_add(i))
+            .collect();
+
+        let builders = opponents
+            .iter()
+            .flat_map(|opponent| {
+                vec![baseline, candidate]
+                    .into_iter()
+                    .map(move |w| (w, opponent))
+            })
+            .map(|(warrior, opponent)| {
+                let mut builder = builder.clone();
+                builder.load_warriors(&[warrior.clone(), opponent.clone()])?;
+                Ok(builder)
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+
+        let rounds = self.runner.run_each_seeded(&builders, &seeds)?;
+
+        let opponents: Vec<OpponentComparison> = opponents
+            .iter()
+            .zip(rounds.chunks(2))
+            .map(|(opponent, rounds)| {
+                OpponentComparison::new(opponent, &rounds[0], &rounds[1], scoring)
+            })
+            .collect();
+
+        let (delta, standard_error) = if opponents.is_empty() {
+            (0.0, 0.0)
+        } else {
+            let n = opponents.len() as f64;
+            (
+                opponents.iter().map(|o| o.delta).sum::<f64>() / n,
+                opponents
+                    .iter()
+                    .map(|o| o.standard_error * o.standard_error)
+                    .sum::<f64>()
+                    .sqrt()
+                    / n,
+            )
+        };
+
+        Ok(ComparisonReport {
+            baseline: baseline.metadata.display_name(),
+            candidate: candidate.metadata.display_name(),
+            rounds: self.rounds,
+            seed,
+            opponents,
+            delta,
+            standard_error,
+            significant: is_significant(delta, standard_error),
+        })
+    }
+}
+
+/// Whether a difference is outside the 95% confidence interval around zero.
+fn is_significant(delta: f64, standard_error: f64) -> bool {
+    delta.abs() > Z_95 * standard_error
+}
+
+/// The scores of both versions against a single opponent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpponentComparison {
+    /// The opponent's name and author.
+    pub name: String,
+    /// The baseline's score against the opponent: the number of points it scored per 100 rounds.
+    pub baseline_score: f64,
+    /// The candidate's score against the opponent.
+    pub candidate_score: f64,
+    /// The candidate's score minus the baseline's score.
+    pub delta: f64,
+    /// The standard error of `delta`, estimated from the paired differences in each round.
+    pub standard_error: f64,
+    /// Whether `delta` is significantly different from zero, at the 95% level.
+    pub significant: bool,
+}
+
+impl OpponentComparison {
+    fn new<S: Scoring + ?Sized>(
+        opponent: &Warrior,
+        baseline: &[RoundResult],
+        candidate: &[RoundResult],
+        scoring: &S,
+    ) -> Self {
+        let mut baseline_points = Sample::new();
+        let mut candidate_points = Sample::new();
+        let mut differences = Sample::new();
+        for (a, b) in baseline.iter().zip(candidate) {
+            let a = scoring.points(a, 0) * 100.0;
+            let b = scoring.points(b, 0) * 100.0;
+            baseline_points.push(a);
+            candidate_points.push(b);
+            differences.push(b - a);
+        }
+
+        Self {
+            name: opponent.metadata.display_name(),
+            baseline_score: baseline_points.mean(),
+            candidate_score: candidate_points.mean(),
+            delta: differences.mean(),
+            standard_error: differences.standard_error(),
+            significant: is_significant(differences.mean(), differences.standard_error()),
+        }
+    }
+}
+
+/// The results of a [`Comparison`](struct.Comparison.html).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    /// The baseline's name and author.
+    pub baseline: String,
+    /// The candidate's name and author.
+    pub candidate: String,
+    /// The number of rounds each version fought against each opponent.
+    pub rounds: usize,
+    /// The seed the rounds' seeds were derived from.
+    pub seed: u64,
+    /// The comparison against each opponent, in the order they were given.
+    pub opponents: Vec<OpponentComparison>,
+    /// The average difference in score over every opponent.
+    pub delta: f64,
+    /// The standard error of `delta`.
+    pub standard_error: f64,
+    /// Whether `delta` is significantly different from zero, at the 95% level.
+    pub significant: bool,
+}
+
+impl Display for ComparisonReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Baseline:  {}", self.baseline)?;
+        writeln!(f, "Candidate: {}", self.candidate)?;
+        writeln!(
+            f,
+            "{:<40} {:>8} {:>9} {:>8} {:>7}",
+            "Opponent", "Baseline", "Candidate", "Delta", "±SE"
+        )?;
+        for opponent in &self.opponents {
+            writeln!(
+                f,
+                "{:<40} {:>8.1} {:>9.1} {:>+8.1} {:>7.2}{}",
+                opponent.name,
+                opponent.baseline_score,
+                opponent.candidate_score,
+                opponent.delta,
+                opponent.standard_error,
+                if opponent.significant { " *" } else { "" }
+            )?;
+        }
+        write!(
+            f,
+            "Overall delta {:+.1} ±{:.2}, {} ({} rounds per opponent, seed {})",
+            self.delta,
+            self.standard_error,
+            if self.significant {
+                "significant at 95%"
+            } else {
+                "not significant"
+            },
+            self.rounds,
+            self.seed
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::score::Standard;
+
+    #[test]
+    fn identical_versions_have_no_difference() {
+        let dwarf = Warrior::parse(include_str!("../warriors/dwarf.red"), 0).unwrap();
+        let stone = Warrior::parse(include_str!("../warriors/stone.red"), 0).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder.cycles_before_tie(8000);
+
+        let report = Comparison::new()
+            .rounds(10)
+            .seed(7)
+            .run(&builder, &dwarf, &dwarf, &[stone], &Standard::new())
+            .unwrap();
+
+        assert_eq!(report.seed, 7);
+        assert_eq!(report.opponents.len(), 1);
+        assert_eq!(
+            report.opponents[0].baseline_score,
+            report.opponents[0].candidate_score
+        );
+        assert_eq!(report.delta, 0.0);
+        assert_eq!(report.standard_error, 0.0);
+        assert!(!report.significant);
+    }
+}
@@ -1,25 +1,27 @@
 use crate::{
     error::CoreError,
-    logger::Logger,
     warrior::{Instruction, Warrior},
 };
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use super::{Core, CoreInstruction};
+use super::{Core, CoreInstruction, Ownership};
 use std::collections::VecDeque;
-#[derive(Debug)]
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+#[derive(Debug, Clone)]
 pub struct CoreBuilder {
-    pub(super) core_size: usize,
-    pub(super) cycles_before_tie: usize,
-    pub(super) initial_instruction: InitialInstruction,
-    pub(super) instruction_limit: usize,
-    pub(super) maximum_number_of_tasks: usize,
-    pub(super) minimum_separation: usize,
-    pub(super) read_distance: usize,
-    pub(super) write_distance: usize,
-    pub(super) separation: Separation,
+    pub(crate) core_size: usize,
+    pub(crate) cycles_before_tie: usize,
+    pub(crate) initial_instruction: InitialInstruction,
+    pub(crate) instruction_limit: usize,
+    pub(crate) maximum_number_of_tasks: usize,
+    pub(crate) minimum_separation: usize,
+    pub(crate) read_distance: usize,
+    pub(crate) write_distance: usize,
+    pub(crate) separation: Separation,
     pub(super) warriors: Vec<Warrior>,
-    pub(super) logger: Option<Box<dyn Logger>>,
+    pub(super) track_ownership: bool,
 }
 
 impl Default for CoreBuilder {
@@ -35,7 +37,7 @@ impl Default for CoreBuilder {
             write_distance: 8000,
             separation: Separation::Random(100),
             warriors: Vec::new(),
-            logger: None,
+            track_ownership: false,
         }
     }
 }
@@ -46,6 +48,29 @@ impl CoreBuilder {
         CoreBuilder::default()
     }
 
+    /// Applies the settings of a standard [`Preset`](enum.Preset.html), replacing the core size,
+    /// cycles before tie, maximum number of tasks, instruction limit, separation and read/write
+    /// distances.
+    pub fn preset(&mut self, preset: Preset) -> &mut Self {
+        let (core_size, cycles_before_tie, maximum_number_of_tasks, instruction_limit, separation) =
+            match preset {
+                Preset::Standard => (8000, 80_000, 8000, 100, 100),
+                Preset::Tiny => (800, 8000, 800, 20, 20),
+                Preset::Nano => (80, 800, 80, 5, 5),
+                Preset::LimitedProcess => (8000, 80_000, 8, 200, 200),
+                Preset::Experimental => (55440, 500_000, 10_000, 200, 200),
+            };
+
+        self.core_size(core_size)
+            .cycles_before_tie(cycles_before_tie)
+            .maximum_number_of_tasks(maximum_number_of_tasks)
+            .instruction_limit(instruction_limit)
+            .minimum_separation(separation)
+            .separation(Separation::Random(separation))
+            .read_distance(core_size)
+            .write_distance(core_size)
+    }
+
     /// Sets the core's size. Core size is the number of instructions which make up the core
     /// during the battle.
     pub fn core_size(&mut self, core_size: usize) -> &mut Self {
@@ -133,6 +158,17 @@ impl CoreBuilder {
         self
     }
 
+    /// Keep track of which warrior last wrote to each cell of the core, which can then be
+    /// queried with [`Core::owner`](../struct.Core.html#method.owner). Off by default, as it
+    /// slows the core down a little.
+    pub fn track_ownership(&mut self, track_ownership: bool) -> &mut Self {
+        self.track_ownership = track_ownership;
+        self
+    }
+
+    /// Sets the warriors to load into the core, in order. Each warrior's `idx` is set to its
+    /// position in `warriors`, so warriors are told apart by position even if they were parsed
+    /// with the same index.
     pub fn load_warriors(&mut self, warriors: &[Warrior]) -> Result<&mut Self, CoreError> {
         for warrior in warriors {
             if warrior.len() > self.instruction_limit {
@@ -149,20 +185,34 @@ impl CoreBuilder {
             };
         }
 
-        self.warriors = warriors.to_vec();
+        // Warriors are identified by their position from here on, in ownership, process
+        // deaths and everything logged, so they are numbered in the order they are loaded.
+        self.warriors = warriors
+            .iter()
+            .enumerate()
+            .map(|(idx, warrior)| Warrior {
+                idx,
+                ..warrior.clone()
+            })
+            .collect();
 
         Ok(self)
     }
 
-    /// Use a `Logger` to log the battle's output.
-    pub fn log_with(&mut self, logger: Box<dyn Logger>) -> &mut Self {
-        self.logger = Some(logger);
-
-        self
+    /// The warriors which will be loaded into the core.
+    pub fn warriors(&self) -> &[Warrior] {
+        &self.warriors
     }
 
     /// Build the core, consuming the `CoreBuilder` and returning a [`Core`](../struct.Core.html) struct.
-    pub fn build(&self) -> Result<Core, CoreError> {
+    pub fn build<'l>(&self) -> Result<Core<'_, 'l>, CoreError> {
+        self.build_with_seed(rand::random())
+    }
+
+    /// Build the core like [`build`](#method.build), but with any random choices, such as the
+    /// warriors' starting positions, made from `seed`. Building with the same settings and seed
+    /// always places the warriors at the same positions, even if the warriors themselves differ.
+    pub fn build_with_seed<'l>(&self, seed: u64) -> Result<Core<'_, 'l>, CoreError> {
         let CoreBuilder {
             initial_instruction,
             separation,
@@ -170,6 +220,7 @@ impl CoreBuilder {
             maximum_number_of_tasks,
             core_size,
             instruction_limit,
+            track_ownership,
             ..
         } = self;
         let mut core_instructions = vec![
@@ -181,40 +232,70 @@ impl CoreBuilder {
         ];
 
         let separation = separation.clone();
+        let mut owners = if *track_ownership {
+            Some(vec![None; *core_size])
+        } else {
+            None
+        };
 
-        let mut warrior_offsets: Vec<usize> = warriors.iter().map(|w| w.starts_at_line).collect();
-        match separation {
+        let loaded_at: Vec<usize> = match separation {
             Separation::Random(min_separation) => {
-                let offsets =
-                    random_offsets(&warriors, min_separation, *instruction_limit, *core_size);
-
-                for (i, (offset, warrior)) in offsets.iter().enumerate() {
-                    let mut ptr = *offset;
-                    warrior_offsets[i] =
-                        Core::fold(warrior_offsets[i] + ptr, *core_size, *core_size);
-                    for instruction in &warrior.instructions {
-                        core_instructions[ptr] =
-                            CoreInstruction::from_instruction(instruction.clone(), *core_size);
-                        ptr = Core::fold(ptr + 1, *core_size, *core_size);
-                    }
-                }
+                let mut rng = StdRng::seed_from_u64(seed);
+                random_offsets(
+                    warriors,
+                    min_separation,
+                    *instruction_limit,
+                    *core_size,
+                    &mut rng,
+                )
+                .into_iter()
+                .map(|(offset, _)| offset)
+                .collect()
             }
             Separation::Fixed(separation) => {
                 let mut ptr = 0_usize;
-                for (i, warrior) in warriors.iter().enumerate() {
-                    warrior_offsets[i] =
-                        Core::fold(warrior_offsets[i] + ptr, *core_size, *core_size);
-                    for instruction in &warrior.instructions {
-                        core_instructions[ptr] =
-                            CoreInstruction::from_instruction(instruction.clone(), *core_size);
-                        ptr = Core::fold(ptr + 1, *core_size, *core_size);
-                    }
-
-                    ptr = Core::fold(ptr + separation, *core_size, *core_size);
+                warriors
+                    .iter()
+                    .map(|warrior| {
+                        let start = ptr;
+                        ptr = Core::fold(ptr + warrior.len() + separation, *core_size, *core_size);
+                        start
+                    })
+                    .collect()
+            }
+            Separation::Positions(positions) => {
+                if positions.len() != warriors.len() {
+                    return Err(CoreError::Positions(warriors.len(), positions.len()));
                 }
+                positions
+                    .iter()
+                    .map(|position| position % core_size)
+                    .collect()
             }
         };
 
+        for (warrior, &start) in warriors.iter().zip(&loaded_at) {
+            let mut ptr = start;
+            for instruction in &warrior.instructions {
+                core_instructions[ptr] =
+                    CoreInstruction::from_instruction(instruction.clone(), *core_size);
+                if let Some(ref mut owners) = owners {
+                    owners[ptr] = Some(Ownership {
+                        warrior: warrior.idx,
+                        cycle: 0,
+                    });
+                }
+                ptr = Core::fold(ptr + 1, *core_size, *core_size);
+            }
+        }
+
+        let warrior_offsets: Vec<usize> = loaded_at
+            .iter()
+            .zip(warriors)
+            .map(|(&start, warrior)| {
+                Core::fold(start + warrior.starts_at_line, *core_size, *core_size)
+            })
+            .collect();
         let task_queues = warrior_offsets
             .iter()
             .zip(warriors)
@@ -233,6 +314,16 @@ impl CoreBuilder {
             task_queues,
             current_queue: 0,
             cycle_count: 0,
+            seed,
+            observing: false,
+            accesses: Vec::new(),
+            loggers: Vec::new(),
+            owners,
+            written: Vec::new(),
+            executing: 0,
+            watching_writes: false,
+            outcome: None,
+            loaded_at,
         })
     }
 }
@@ -241,10 +332,62 @@ impl CoreBuilder {
 ///
 /// The number of instructions from the first instruction of one warrior to the first instruction of the next warrior.
 /// If a core's separation is `Random`, separations will be chosen randomly from the set of numbers larger than the core's minimum separation.
-#[derive(Debug, Clone)]
+/// If it is `Positions`, each warrior is loaded with its first instruction at the given address,
+/// in the order the warriors were loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Separation {
     Random(usize),
     Fixed(usize),
+    Positions(Vec<usize>),
+}
+
+/// Standard sets of core settings, as used by the popular King-of-the-Hill servers.
+///
+/// | Preset           | Core size | Cycles  | Tasks  | Length | Separation |
+/// |------------------|-----------|---------|--------|--------|------------|
+/// | `Standard`       | 8000      | 80000   | 8000   | 100    | 100        |
+/// | `Tiny`           | 800       | 8000    | 800    | 20     | 20         |
+/// | `Nano`           | 80        | 800     | 80     | 5      | 5          |
+/// | `LimitedProcess` | 8000      | 80000   | 8      | 200    | 200        |
+/// | `Experimental`   | 55440     | 500000  | 10000  | 200    | 200        |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preset {
+    Standard,
+    Tiny,
+    Nano,
+    LimitedProcess,
+    Experimental,
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Preset::Standard => "standard",
+                Preset::Tiny => "tiny",
+                Preset::Nano => "nano",
+                Preset::LimitedProcess => "lp",
+                Preset::Experimental => "experimental",
+            }
+        )
+    }
+}
+
+impl FromStr for Preset {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" | "94" | "94nop" => Ok(Preset::Standard),
+            "tiny" => Ok(Preset::Tiny),
+            "nano" => Ok(Preset::Nano),
+            "lp" | "limitedprocess" => Ok(Preset::LimitedProcess),
+            "experimental" | "94x" => Ok(Preset::Experimental),
+            _ => Err(CoreError::UnknownPreset(s.to_owned())),
+        }
+    }
 }
 
 /// The value to which the core's memory addresses are initialised
@@ -269,12 +412,13 @@ impl InitialInstruction {
     }
 }
 
-fn random_offsets(
-    warriors: &[Warrior],
+fn random_offsets<'a, R: Rng>(
+    warriors: &'a [Warrior],
     minimum_separation: usize,
     instruction_limit: usize,
     core_size: usize,
-) -> Vec<(usize, &Warrior)> {
+    rng: &mut R,
+) -> Vec<(usize, &'a Warrior)> {
     let mut offsets: Vec<(usize, &Warrior)> = Vec::new();
 
     for warrior in warriors {
@@ -284,6 +428,7 @@ fn random_offsets(
             minimum_separation,
             instruction_limit,
             core_size,
+            rng,
         );
         offsets.push((offset, warrior));
     }
@@ -291,11 +436,12 @@ fn random_offsets(
     offsets
 }
 
-fn get_valid_address(
+fn get_valid_address<R: Rng>(
     offsets: &[usize],
     minimum_separation: usize,
     instruction_limit: usize,
     core_size: usize,
+    rng: &mut R,
 ) -> usize {
     let diff = |x, y| {
         if x > y {
@@ -307,8 +453,6 @@ fn get_valid_address(
 
     let ptr: usize;
 
-    let mut rng = rand::thread_rng();
-
     // This will run forever if we can't fit a warrior...
     'outer: loop {
         let address: usize = rng.gen_range(0, core_size);
@@ -334,6 +478,40 @@ mod test {
     use super::*;
     use std::convert::TryFrom;
 
+    #[test]
+    fn presets() {
+        let mut builder = CoreBuilder::new();
+        builder.preset("nano".parse().unwrap());
+        assert_eq!(builder.core_size, 80);
+        assert_eq!(builder.cycles_before_tie, 800);
+        assert_eq!(builder.instruction_limit, 5);
+        assert_eq!(builder.read_distance, 80);
+
+        let preset: Preset = "LP".parse().unwrap();
+        assert_eq!(preset, Preset::LimitedProcess);
+        assert_eq!(preset.to_string().parse::<Preset>().unwrap(), preset);
+        assert!("huge".parse::<Preset>().is_err());
+    }
+
+    #[test]
+    fn seeded_builds_are_repeatable() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let stone = Warrior::parse(include_str!("../../warriors/stone.red"), 1).unwrap();
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder.load_warriors(&[imp.clone(), stone]).unwrap();
+        let first = builder.build_with_seed(42).unwrap();
+        let second = builder.build_with_seed(42).unwrap();
+        assert_eq!(first.seed(), 42);
+        assert_eq!(first.instructions(), second.instructions());
+
+        let mut other = CoreBuilder::new();
+        other.load_warriors(&[imp, dwarf]).unwrap();
+        let third = other.build_with_seed(42).unwrap();
+        assert_eq!(first.task_queues()[0].1, third.task_queues()[0].1);
+    }
+
     #[test]
     fn random_addresses() {
         let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
@@ -345,7 +523,7 @@ mod test {
         let warriors = vec![imp, stone, imp2, stone2, imp3, stone3];
 
         for _ in 0..5000 {
-            let offsets = random_offsets(&warriors, 100, 100, 8000);
+            let offsets = random_offsets(&warriors, 100, 100, 8000, &mut rand::thread_rng());
 
             assert_eq!(offsets.len(), 6);
 
@@ -1,13 +1,20 @@
 mod corebuilder;
+mod snapshot;
 pub use corebuilder::*;
+pub use snapshot::*;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter};
 
-use crate::{logger::GameEvent, parser::instruction::Modifier, parser::instruction::Opcode};
+use crate::{
+    logger::{AccessKind, CellAccess, DeathCause, Execution, GameEvent, Logger, ProcessDeath},
+    parser::instruction::Modifier,
+    parser::instruction::Opcode,
+};
 use crate::{
     parser::instruction::AddressMode,
     warrior::{Instruction, Warrior},
 };
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
 /// The result of the execution of a single core instruction.
@@ -28,8 +35,23 @@ pub enum CoreChange {
     WarriorKilled(usize),
 }
 
+/// An iterator over the changes made to a core as it runs, created by
+/// [`Core::changes`](struct.Core.html#method.changes).
+#[derive(Debug)]
+pub struct Changes<'c, 'a, 'l> {
+    core: &'c mut Core<'a, 'l>,
+}
+
+impl Iterator for Changes<'_, '_, '_> {
+    type Item = CoreChange;
+
+    fn next(&mut self) -> Option<CoreChange> {
+        self.core.step()
+    }
+}
+
 /// Like a warrior instruction, but its addresses are usize rather than i32
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CoreInstruction {
     opcode: Opcode,
     modifier: Modifier,
@@ -54,13 +76,39 @@ fn keep_in_bounds(input: i64, m: usize) -> usize {
     let m = i64::try_from(m).unwrap();
 
     while i < 0 {
-        i += m as i64;
+        i += m;
     }
 
     (i % m) as usize // Safe coercion, can't under/overflow because clamped between 0 and m.
 }
 
 impl CoreInstruction {
+    pub fn opcode(&self) -> &Opcode {
+        &self.opcode
+    }
+
+    pub fn modifier(&self) -> &Modifier {
+        &self.modifier
+    }
+
+    pub fn mode_a(&self) -> AddressMode {
+        self.mode_a
+    }
+
+    /// The A field, as an offset between 0 and the core size.
+    pub fn addr_a(&self) -> usize {
+        self.addr_a
+    }
+
+    pub fn mode_b(&self) -> AddressMode {
+        self.mode_b
+    }
+
+    /// The B field, as an offset between 0 and the core size.
+    pub fn addr_b(&self) -> usize {
+        self.addr_b
+    }
+
     fn from_instruction(instruction: Instruction, core_size: usize) -> Self {
         Self {
             opcode: instruction.opcode,
@@ -94,16 +142,42 @@ impl Display for MatchOutcome<'_> {
     }
 }
 
+/// The warrior that last wrote to a cell of the core, and the cycle it did so on. Cells a
+/// warrior was loaded into are owned by it from cycle 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ownership {
+    pub warrior: usize,
+    pub cycle: usize,
+}
+
 #[derive(Debug)]
-pub struct Core<'a> {
+pub struct Core<'a, 'l> {
     core: &'a CoreBuilder,
     instructions: Vec<CoreInstruction>,
     task_queues: VecDeque<(&'a Warrior, VecDeque<usize>)>,
     current_queue: usize,
     cycle_count: usize,
+    seed: u64,
+    /// Whether cell accesses are being recorded for a logger, during `run_once`.
+    observing: bool,
+    accesses: Vec<CellAccess>,
+    loggers: Vec<&'l mut dyn Logger>,
+    /// The owner of each cell, if ownership is being tracked.
+    owners: Option<Vec<Option<Ownership>>>,
+    /// The cells written by the instruction being executed, if ownership is being tracked.
+    written: Vec<usize>,
+    /// The index of the warrior whose instruction is being executed.
+    executing: usize,
+    /// Whether the cells written by each instruction are being recorded, even though ownership
+    /// isn't being tracked.
+    watching_writes: bool,
+    /// The outcome of the match, once it is over.
+    outcome: Option<MatchOutcome<'a>>,
+    /// The address each warrior's first instruction was loaded at, in the order they were loaded.
+    loaded_at: Vec<usize>,
 }
 
-impl<'a> Core<'a> {
+impl<'a, 'l> Core<'a, 'l> {
     /// Create a `CoreBuilder`, in order to configure and build the core.
     pub fn builder() -> CoreBuilder {
         CoreBuilder::new()
@@ -113,8 +187,93 @@ impl<'a> Core<'a> {
         &self.instructions
     }
 
-    pub fn task_queues(&self) -> &[(&'a Warrior, VecDeque<usize>)] {
-        self.task_queues.as_slices().0
+    /// The task queue of each warrior still alive, in the order they will next run.
+    pub fn task_queues(&self) -> &VecDeque<(&'a Warrior, VecDeque<usize>)> {
+        &self.task_queues
+    }
+
+    /// The builder the core was built from.
+    pub(crate) fn settings(&self) -> &'a CoreBuilder {
+        self.core
+    }
+
+    /// The seed the core's random choices were made from when it was built.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Attach a `Logger`, which will be told about everything that happens in the core from now
+    /// on. Any number of loggers can be attached. The logger is borrowed for as long as the core
+    /// lives, so whatever it has collected can be read from it once the core is dropped. The
+    /// borrow is separate from the builder's, so outcomes of the match can still be used then.
+    pub fn log_with(&mut self, logger: &'l mut dyn Logger) -> &mut Self {
+        self.loggers.push(logger);
+
+        self
+    }
+
+    /// The warrior that last wrote to the cell at `address`, if ownership is being tracked (see
+    /// [`CoreBuilder::track_ownership`](struct.CoreBuilder.html#method.track_ownership)) and
+    /// any warrior has.
+    pub fn owner(&self, address: usize) -> Option<Ownership> {
+        self.owners
+            .as_ref()
+            .and_then(|owners| owners.get(address).copied().flatten())
+    }
+
+    /// The owner of every cell of the core, or `None` if ownership isn't being tracked.
+    pub fn owners(&self) -> Option<&[Option<Ownership>]> {
+        self.owners.as_deref()
+    }
+
+    /// The number of cells owned by the warrior with index `warrior`, or 0 if ownership isn't
+    /// being tracked.
+    pub fn cells_owned_by(&self, warrior: usize) -> usize {
+        self.owners().map_or(0, |owners| {
+            owners
+                .iter()
+                .filter(|owner| owner.is_some_and(|o| o.warrior == warrior))
+                .count()
+        })
+    }
+
+    /// The cells written to by the last instruction executed, if ownership is being tracked or
+    /// the core is running until a cell is written (see [`run_until_write`](#method.run_until_write)).
+    pub fn last_written(&self) -> &[usize] {
+        &self.written
+    }
+
+    /// The address each warrior's first instruction was loaded at, in the order the warriors
+    /// were loaded.
+    pub fn loaded_at(&self) -> &[usize] {
+        &self.loaded_at
+    }
+
+    /// The warrior whose instruction will be executed next, and the address of the instruction.
+    pub fn next_process(&self) -> Option<(&'a Warrior, usize)> {
+        self.task_queues
+            .front()
+            .and_then(|(warrior, queue)| Some((*warrior, *queue.front()? % self.core.core_size)))
+    }
+
+    /// Overwrites the cell at `address` with `instruction`.
+    pub fn set_instruction(&mut self, address: usize, instruction: &Instruction) {
+        let address = address % self.core.core_size;
+        self.instructions[address] =
+            CoreInstruction::from_instruction(instruction.clone(), self.core.core_size);
+    }
+
+    /// Makes the warrior with index `warrior` the next to execute an instruction, with the other
+    /// warriors following in the same order as before. Returns false if no warrior with that
+    /// index is still alive.
+    pub fn switch_to(&mut self, warrior: usize) -> bool {
+        match self.task_queues.iter().position(|(w, _)| w.idx == warrior) {
+            Some(position) => {
+                self.task_queues.rotate_left(position);
+                true
+            }
+            None => false,
+        }
     }
 
     /// The core's current cycle count.
@@ -132,25 +291,106 @@ impl<'a> Core<'a> {
         result
     }
 
-    pub fn run(&mut self) -> MatchOutcome {
-        while let ExecutionOutcome::Continue(_) = self.run_once() {
-            if let Some(ref logger) = self.core.logger {
-                logger.log(&self, GameEvent::Continue);
+    /// Runs the match to the end and returns its outcome.
+    pub fn run(&mut self) -> MatchOutcome<'a> {
+        while self.step().is_some() {}
+
+        self.outcome
+            .clone()
+            .expect("the match is over once the core stops stepping")
+    }
+
+    /// Runs at most `cycles` more cycles, returning the outcome of the match if it ends first.
+    pub fn run_for(&mut self, cycles: usize) -> Option<MatchOutcome<'a>> {
+        if cycles == 0 {
+            return self.outcome.clone();
+        }
+        let end = self.cycle_count + cycles;
+
+        self.run_until(|core, _| core.cycle_count() >= end)
+    }
+
+    /// Runs until `stop` returns true for the core and the change just made to it, returning the
+    /// outcome of the match if it ends first.
+    pub fn run_until<F>(&mut self, mut stop: F) -> Option<MatchOutcome<'a>>
+    where
+        F: FnMut(&Core<'a, 'l>, &CoreChange) -> bool,
+    {
+        while let Some(change) = self.step() {
+            if stop(self, &change) {
+                return None;
             }
         }
 
-        let warriors: Vec<&Warrior> = self.task_queues.iter().map(|(w, _)| *w).collect();
+        self.outcome.clone()
+    }
+
+    /// Runs until a warrior is killed, returning the outcome of the match if it ends first. The
+    /// kill that ends the match ends the run with the match's outcome.
+    pub fn run_until_kill(&mut self) -> Option<MatchOutcome<'a>> {
+        self.run_until(|_, change| matches!(change, CoreChange::WarriorKilled(_)))
+    }
+
+    /// Runs until an instruction writes to the cell at `address`, returning the outcome of the
+    /// match if it ends first. Decrements and increments of the cell's fields count as writes.
+    pub fn run_until_write(&mut self, address: usize) -> Option<MatchOutcome<'a>> {
+        let address = address % self.core.core_size;
 
-        let outcome = match warriors.len() {
+        self.watching_writes(|core| {
+            core.run_until(|core, _| core.last_written().contains(&address))
+        })
+    }
+
+    /// Calls `run` with the cells written by each instruction recorded in
+    /// [`last_written`](#method.last_written).
+    pub(crate) fn watching_writes<T>(&mut self, run: impl FnOnce(&mut Self) -> T) -> T {
+        let watching = std::mem::replace(&mut self.watching_writes, true);
+        let result = run(self);
+        self.watching_writes = watching;
+
+        result
+    }
+
+    /// An iterator over the changes made to the core by each instruction, which runs the match
+    /// until it is over. The outcome can then be read with [`outcome`](#method.outcome).
+    pub fn changes(&mut self) -> Changes<'_, 'a, 'l> {
+        Changes { core: self }
+    }
+
+    /// The outcome of the match, if it is over.
+    pub fn outcome(&self) -> Option<&MatchOutcome<'a>> {
+        self.outcome.as_ref()
+    }
+
+    /// Runs a single instruction, returning `None` once the match is over.
+    fn step(&mut self) -> Option<CoreChange> {
+        match self.run_once() {
+            ExecutionOutcome::Continue(change) => Some(change),
+            ExecutionOutcome::GameOver => None,
+        }
+    }
+
+    /// The outcome of the match if it were to end now.
+    fn survivors(&self) -> MatchOutcome<'a> {
+        let warriors: Vec<&'a Warrior> = self.task_queues.iter().map(|(w, _)| *w).collect();
+
+        match warriors.len() {
             1 => MatchOutcome::Win(warriors[0]),
             _ => MatchOutcome::Draw(warriors),
-        };
+        }
+    }
 
-        if let Some(ref logger) = self.core.logger {
-            logger.log(self, GameEvent::GameOver(outcome.clone()));
+    fn log(&mut self, event: GameEvent<'a>) {
+        if self.loggers.is_empty() {
+            return;
         }
 
-        outcome
+        // The loggers are taken out of the core while they run, as they need to see it.
+        let mut loggers = std::mem::take(&mut self.loggers);
+        for logger in loggers.iter_mut() {
+            logger.log(self, &event);
+        }
+        self.loggers = loggers;
     }
 
     fn subtract(first: usize, second: usize, limit: usize) -> usize {
@@ -174,8 +414,44 @@ impl<'a> Core<'a> {
         }
     }
 
+    fn record(&mut self, address: usize, kind: AccessKind) {
+        if self.observing {
+            self.accesses.push(CellAccess { address, kind });
+        }
+        if kind == AccessKind::Read {
+            return;
+        }
+        if let Some(ref mut owners) = self.owners {
+            owners[address] = Some(Ownership {
+                warrior: self.executing,
+                cycle: self.cycle_count,
+            });
+        }
+        if self.owners.is_some() || self.watching_writes {
+            self.written.push(address);
+        }
+    }
+
     fn evaluate_operand(&mut self, mode: AddressMode, addr: usize, task: usize) -> usize {
         // println!("Evaluating: {} {} at task {}", mode, addr, task);
+        if (self.observing || self.owners.is_some() || self.watching_writes)
+            && mode != AddressMode::Immediate
+            && mode != AddressMode::Direct
+        {
+            let next = Core::fold(addr + task, self.core.read_distance, self.core.core_size);
+            let kind = match mode {
+                AddressMode::AFieldPredecrementIndirect
+                | AddressMode::BFieldPredecrementIndirect => AccessKind::Decrement,
+                AddressMode::AFieldPostincrementIndirect
+                | AddressMode::BFieldPostincrementIndirect => AccessKind::Increment,
+                _ => AccessKind::Read,
+            };
+            self.record(next, AccessKind::Read);
+            if kind != AccessKind::Read {
+                self.record(next, kind);
+            }
+        }
+
         match mode {
             AddressMode::Immediate => task,
             AddressMode::Direct => {
@@ -240,11 +516,31 @@ impl<'a> Core<'a> {
         }
     }
 
+    /// Runs a single instruction, logging it as `run` does. Once the match is over, its outcome
+    /// is decided and logged, and running again does nothing but return
+    /// `ExecutionOutcome::GameOver`.
     pub fn run_once(&mut self) -> ExecutionOutcome {
-        let instruction_register: CoreInstruction;
-        let source_register: CoreInstruction;
-        let destination_register: CoreInstruction;
+        if self.outcome.is_some() {
+            return ExecutionOutcome::GameOver;
+        }
 
+        match self.execute() {
+            ExecutionOutcome::Continue(change) => {
+                self.log(GameEvent::Continue);
+                ExecutionOutcome::Continue(change)
+            }
+            ExecutionOutcome::GameOver => {
+                let outcome = self.survivors();
+
+                self.log(GameEvent::GameOver(outcome.clone()));
+                self.outcome = Some(outcome);
+                ExecutionOutcome::GameOver
+            }
+        }
+    }
+
+    /// Executes the next instruction, without deciding the outcome if the match is over.
+    fn execute(&mut self) -> ExecutionOutcome {
         let read_distance = self.core.read_distance;
         let write_distance = self.core.write_distance;
         let core_size = self.core.core_size;
@@ -252,18 +548,21 @@ impl<'a> Core<'a> {
         let fold_write = |x| Core::fold(x, write_distance, core_size);
         let decrement = |x| Core::decrement_address(x, write_distance);
 
+        self.observing = !self.loggers.is_empty();
+        self.accesses.clear();
+        self.written.clear();
+
         // Unwrap because this function won't be run when empty... Maybe this is not true.
         let mut current = self.task_queues.pop_front().unwrap();
         let current_queue = &mut current.1;
         let warrior_idx = current.0.idx;
+        self.executing = warrior_idx;
 
         // Get the task, killing the warrior if it has no tasks.
         let task = match current_queue.pop_front() {
             Some(v) => v,
             None => {
-                if let Some(ref logger) = self.core.logger {
-                    logger.log(self, GameEvent::WarriorKilled(current.0));
-                }
+                self.log(GameEvent::WarriorKilled(current.0));
 
                 return if self.task_queues.len() <= 1 {
                     ExecutionOutcome::GameOver
@@ -274,7 +573,11 @@ impl<'a> Core<'a> {
         };
 
         // Copy the instruction pointed to by the task to the IR.
-        instruction_register = self.instructions[fold_read(task)].clone();
+        let instruction_register: CoreInstruction = self.instructions[fold_read(task)].clone();
+        // The instruction's writer is taken now, as executing it may write over it.
+        let writer = self.owner(fold_read(task));
+        self.record(fold_read(task), AccessKind::Read);
+        let queued_before = current_queue.len();
 
         // Evaluate the IR's A operand and put the resolved instruction in the source register.
         let source_ptr = self.evaluate_operand(
@@ -282,7 +585,8 @@ impl<'a> Core<'a> {
             instruction_register.addr_a,
             task,
         );
-        source_register = self.instructions[fold_read(source_ptr)].clone();
+        let source_register: CoreInstruction = self.instructions[fold_read(source_ptr)].clone();
+        self.record(fold_read(source_ptr), AccessKind::Read);
 
         // Evaluate the IR's B operand and put the resolved instruction in the destination register.
         let destination_ptr = self.evaluate_operand(
@@ -291,7 +595,15 @@ impl<'a> Core<'a> {
             task,
         );
 
-        destination_register = self.instructions[destination_ptr].clone();
+        let destination_register: CoreInstruction = self.instructions[destination_ptr].clone();
+        self.record(destination_ptr, AccessKind::Read);
+        match instruction_register.opcode {
+            Opcode::Mov | Opcode::Add | Opcode::Sub | Opcode::Mul => {
+                self.record(destination_ptr, AccessKind::Write)
+            }
+            Opcode::Djn => self.record(destination_ptr, AccessKind::Decrement),
+            _ => {}
+        }
 
         match instruction_register.opcode {
             Opcode::Dat => {}
@@ -462,87 +774,55 @@ impl<'a> Core<'a> {
                 }
                 current_queue.push_back(fold_write(task + 1))
             }
-            Opcode::Div => {
-                match instruction_register.modifier {
-                    Modifier::A => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a / source_register.addr_a,
-                        );
-                    }
-                    Modifier::B => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b / source_register.addr_b,
-                        );
-                    }
-                    Modifier::AB => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b / source_register.addr_a,
-                        );
-                    }
-                    Modifier::BA => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a / source_register.addr_b,
-                        );
+            Opcode::Div | Opcode::Mod => {
+                let divide = |dividend: usize, divisor: usize| {
+                    if instruction_register.opcode == Opcode::Div {
+                        dividend.checked_div(divisor)
+                    } else {
+                        dividend.checked_rem(divisor)
                     }
+                };
+                // The divisor of the destination's A and B fields, if they're divided.
+                let (divisor_a, divisor_b) = match instruction_register.modifier {
+                    Modifier::A => (Some(source_register.addr_a), None),
+                    Modifier::B => (None, Some(source_register.addr_b)),
+                    Modifier::AB => (None, Some(source_register.addr_a)),
+                    Modifier::BA => (Some(source_register.addr_b), None),
                     Modifier::F | Modifier::I => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a / source_register.addr_a,
-                        );
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b / source_register.addr_b,
-                        );
+                        (Some(source_register.addr_a), Some(source_register.addr_b))
                     }
-                    Modifier::X => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b / source_register.addr_a,
-                        );
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a / source_register.addr_b,
-                        );
+                    Modifier::X => (Some(source_register.addr_b), Some(source_register.addr_a)),
+                };
+
+                // Dividing a field by zero leaves it unchanged and kills the process, but any
+                // other field is still divided.
+                let mut divided_by_zero = false;
+                let mut written = false;
+                if let Some(divisor) = divisor_a {
+                    match divide(self.instructions[destination_ptr].addr_a, divisor) {
+                        Some(value) => {
+                            self.instructions[destination_ptr].addr_a = fold_write(value);
+                            written = true;
+                        }
+                        None => divided_by_zero = true,
                     }
                 }
-                current_queue.push_back(fold_write(task + 1))
-            }
-            Opcode::Mod => {
-                match instruction_register.modifier {
-                    Modifier::A => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a % source_register.addr_a,
-                        );
-                    }
-                    Modifier::B => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b % source_register.addr_b,
-                        );
-                    }
-                    Modifier::AB => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b % source_register.addr_a,
-                        );
-                    }
-                    Modifier::BA => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a % source_register.addr_b,
-                        );
-                    }
-                    Modifier::F | Modifier::I => {
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a % source_register.addr_a,
-                        );
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b % source_register.addr_b,
-                        );
-                    }
-                    Modifier::X => {
-                        self.instructions[destination_ptr].addr_b = fold_write(
-                            self.instructions[destination_ptr].addr_b % source_register.addr_a,
-                        );
-                        self.instructions[destination_ptr].addr_a = fold_write(
-                            self.instructions[destination_ptr].addr_a % source_register.addr_b,
-                        );
+                if let Some(divisor) = divisor_b {
+                    match divide(self.instructions[destination_ptr].addr_b, divisor) {
+                        Some(value) => {
+                            self.instructions[destination_ptr].addr_b = fold_write(value);
+                            written = true;
+                        }
+                        None => divided_by_zero = true,
                     }
                 }
-                current_queue.push_back(fold_write(task + 1))
+
+                if written {
+                    self.record(destination_ptr, AccessKind::Write);
+                }
+                if !divided_by_zero {
+                    current_queue.push_back(fold_write(task + 1))
+                }
             }
             Opcode::Jmp => current_queue.push_back(source_ptr),
             Opcode::Jmz => match instruction_register.modifier {
@@ -705,7 +985,7 @@ impl<'a> Core<'a> {
             Opcode::Spl => {
                 current_queue.push_back(fold_write(task + 1));
                 if current_queue.len() < self.core.maximum_number_of_tasks {
-                    current_queue.push_back(source_register.addr_a);
+                    current_queue.push_back(source_ptr);
                 }
             }
             Opcode::Nop => current_queue.push_back(fold_write(task + 1)),
@@ -717,8 +997,47 @@ impl<'a> Core<'a> {
             self.current_queue + 1
         };
 
+        let execution = if self.observing {
+            Some(Execution {
+                warrior: current.0,
+                pc: fold_read(task),
+                instruction: instruction_register.clone(),
+                a_ptr: fold_read(source_ptr),
+                b_ptr: destination_ptr,
+                accesses: self.accesses.clone(),
+                queued: current_queue.iter().skip(queued_before).copied().collect(),
+            })
+        } else {
+            None
+        };
+
+        let death = if current_queue.len() == queued_before {
+            Some(ProcessDeath {
+                warrior: current.0,
+                pc: fold_read(task),
+                cause: match (&instruction_register.opcode, writer) {
+                    (Opcode::Div, _) | (Opcode::Mod, _) => DeathCause::DivideByZero,
+                    (_, Some(writer)) => DeathCause::Warrior(writer.warrior),
+                    (_, None) if self.owners.is_some() => DeathCause::InitialCore,
+                    (_, None) => DeathCause::Unknown,
+                },
+                writer,
+                instruction: instruction_register.clone(),
+                cycle: self.cycle_count,
+            })
+        } else {
+            None
+        };
+
         self.task_queues.push_back(current);
 
+        if let Some(execution) = execution {
+            self.log(GameEvent::Executed(execution));
+        }
+        if let Some(death) = death {
+            self.log(GameEvent::ProcessKilled(death));
+        }
+
         self.cycle_count += 1;
         if self.cycle_count >= self.core.cycles_before_tie {
             return ExecutionOutcome::GameOver;
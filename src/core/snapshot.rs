@@ -0,0 +1,172 @@
+use super::{Core, CoreBuilder, CoreInstruction, Ownership};
+use crate::error::CoreError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// The processes of a warrior still alive when a snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotQueue {
+    /// The warrior's position in the order the warriors were loaded.
+    pub warrior: usize,
+    /// The addresses of the warrior's processes, in the order they will run.
+    pub processes: Vec<usize>,
+}
+
+/// The full state of a match at a point in it, which a core can be restored to in order to
+/// rewind the match or to try something different from that point.
+///
+/// A snapshot only holds what changes as a match runs. The warriors and the core's settings
+/// come from the builder it is restored with, which should be the one the snapshot's core was
+/// built from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The seed the core was built from.
+    pub seed: u64,
+    pub cycle_count: usize,
+    pub current_queue: usize,
+    /// Every cell of the core, from address 0.
+    pub cells: Vec<CoreInstruction>,
+    /// The owner of every cell, if ownership was being tracked.
+    pub owners: Option<Vec<Option<Ownership>>>,
+    /// The queues of the warriors still alive, in the order they will next run.
+    pub task_queues: Vec<SnapshotQueue>,
+    /// The address each warrior was loaded at, in the order they were loaded.
+    pub loaded_at: Vec<usize>,
+    /// Whether the match was over.
+    pub over: bool,
+}
+
+impl<'a> Core<'a, '_> {
+    /// Takes a snapshot of the match as it stands.
+    pub fn snapshot(&self) -> Snapshot {
+        let warriors = &self.core.warriors;
+
+        Snapshot {
+            seed: self.seed,
+            cycle_count: self.cycle_count,
+            current_queue: self.current_queue,
+            cells: self.instructions.clone(),
+            owners: self.owners.clone(),
+            task_queues: self
+                .task_queues
+                .iter()
+                .map(|(warrior, queue)| SnapshotQueue {
+                    warrior: warriors
+                        .iter()
+                        .position(|w| std::ptr::eq(w, *warrior))
+                        .expect("every warrior in the core was loaded by its builder"),
+                    processes: queue.iter().copied().collect(),
+                })
+                .collect(),
+            loaded_at: self.loaded_at.clone(),
+            over: self.outcome.is_some(),
+        }
+    }
+
+    /// Puts the match back in the state `snapshot` was taken in. Any loggers stay attached.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), CoreError> {
+        let core_size = self.core.core_size;
+        let warriors = &self.core.warriors;
+        if snapshot.cells.len() != core_size {
+            return Err(CoreError::SnapshotMismatch("wrong number of cells"));
+        }
+        if snapshot
+            .owners
+            .as_ref()
+            .is_some_and(|owners| owners.len() != core_size)
+        {
+            return Err(CoreError::SnapshotMismatch("wrong number of owners"));
+        }
+        if snapshot.loaded_at.len() != warriors.len() {
+            return Err(CoreError::SnapshotMismatch("wrong number of warriors"));
+        }
+
+        let mut task_queues = VecDeque::with_capacity(snapshot.task_queues.len());
+        for queue in &snapshot.task_queues {
+            let warrior = warriors
+                .get(queue.warrior)
+                .ok_or(CoreError::SnapshotMismatch("unknown warrior"))?;
+            if queue.processes.iter().any(|&address| address >= core_size) {
+                return Err(CoreError::SnapshotMismatch("process outside the core"));
+            }
+            task_queues.push_back((warrior, queue.processes.iter().copied().collect()));
+        }
+
+        self.seed = snapshot.seed;
+        self.cycle_count = snapshot.cycle_count;
+        self.current_queue = snapshot.current_queue;
+        self.instructions = snapshot.cells.clone();
+        self.owners = snapshot.owners.clone();
+        self.task_queues = task_queues;
+        self.loaded_at = snapshot.loaded_at.clone();
+        self.accesses.clear();
+        self.written.clear();
+        self.outcome = if snapshot.over {
+            Some(self.survivors())
+        } else {
+            None
+        };
+
+        Ok(())
+    }
+}
+
+impl CoreBuilder {
+    /// Builds a core in the state `snapshot` was taken in, so that the match can be resumed
+    /// from there. The builder should have the same settings and warriors as the one the
+    /// snapshot's core was built from.
+    pub fn restore<'l>(&self, snapshot: &Snapshot) -> Result<Core<'_, 'l>, CoreError> {
+        let mut core = self.build_with_seed(snapshot.seed)?;
+        core.restore(snapshot)?;
+
+        Ok(core)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::Separation, warrior::Warrior};
+
+    #[test]
+    fn restores_snapshots() {
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 1).unwrap();
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(4000)
+            .separation(Separation::Fixed(1000))
+            .track_ownership(true)
+            .load_warriors(&[dwarf, imp])
+            .unwrap();
+
+        let mut core = builder.build().unwrap();
+        core.run_for(500);
+        let snapshot = core.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let outcome = core.run();
+        let end = core.snapshot();
+        assert!(end.over);
+
+        // Rewinding in place, and forking a new core from the saved snapshot, both finish the
+        // match the same way.
+        core.restore(&snapshot).unwrap();
+        assert_eq!(core.cycle_count(), 500);
+        assert_eq!(core.outcome(), None);
+        assert_eq!(core.run(), outcome);
+
+        let saved: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(saved, snapshot);
+        let mut fork = builder.restore(&saved).unwrap();
+        assert_eq!(fork.snapshot(), snapshot);
+        assert_eq!(fork.run(), outcome);
+        assert_eq!(fork.snapshot(), end);
+
+        let mut small = builder.clone();
+        small.core_size(100);
+        assert!(matches!(
+            small.restore(&saved),
+            Err(CoreError::SnapshotMismatch(_))
+        ));
+    }
+}
@@ -151,18 +151,18 @@ fn armadillo_builds() {
 #[test]
 fn imp_fight() {
     let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
-    let imp2 = imp.clone();
-    let warriors = vec![imp.clone(), imp2.clone()];
+    let warriors = vec![imp.clone(), imp];
 
     let mut cb = CoreBuilder::new();
-    let mut core = cb
-        .separation(Separation::Fixed(500))
+    cb.separation(Separation::Fixed(500))
         .load_warriors(&warriors)
-        .unwrap()
-        .build()
         .unwrap();
+    // The two copies are told apart by the order they were loaded in.
+    let loaded = cb.warriors();
+    assert_eq!((loaded[0].idx, loaded[1].idx), (0, 1));
+    let mut core = cb.build().unwrap();
 
-    assert_eq!(core.run(), MatchOutcome::Draw(vec![&imp, &imp2]));
+    assert_eq!(core.run(), MatchOutcome::Draw(vec![&loaded[0], &loaded[1]]));
 }
 
 #[test]
@@ -300,20 +300,18 @@ fn build_and_run_stone() {
 #[test]
 fn imp_gate_vs_imp() {
     let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
-    let imp_gate = Warrior::parse(include_str!("../../warriors/impgate.red"), 0).unwrap();
+    let imp_gate = Warrior::parse(include_str!("../../warriors/impgate.red"), 1).unwrap();
     let warriors = vec![imp.clone(), imp_gate.clone()];
 
-    let logger = crate::logger::DebugLogger::new();
+    let mut logger = crate::logger::DebugLogger::new();
 
     let mut cb = CoreBuilder::new();
-    let mut core = cb
-        .load_warriors(&warriors)
-        .unwrap()
-        .log_with(Box::new(logger))
-        .build()
-        .unwrap();
+    let mut core = cb.load_warriors(&warriors).unwrap().build().unwrap();
 
-    assert_eq!(core.run(), MatchOutcome::Win(&imp_gate));
+    assert_eq!(
+        core.log_with(&mut logger).run(),
+        MatchOutcome::Win(&imp_gate)
+    );
 }
 #[test]
 fn wait_vs_armadillo() {
@@ -321,18 +319,20 @@ fn wait_vs_armadillo() {
     let wait = Warrior::parse(include_str!("../../warriors/wait.red"), 0).unwrap();
     let warriors = vec![armadillo.clone(), wait.clone()];
 
-    let logger = crate::logger::DebugLogger::new();
+    let mut logger = crate::logger::DebugLogger::new();
 
     let mut cb = CoreBuilder::new();
     let mut core = cb
         .core_size(8000)
         .load_warriors(&warriors)
         .unwrap()
-        .log_with(Box::new(logger))
         .build()
         .unwrap();
 
-    assert_eq!(core.run(), MatchOutcome::Win(&armadillo));
+    assert_eq!(
+        core.log_with(&mut logger).run(),
+        MatchOutcome::Win(&armadillo)
+    );
 }
 
 #[test]
@@ -342,16 +342,276 @@ fn stone_vs_dwarf_vs_imp() {
     let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
     let warriors = vec![stone.clone(), imp.clone(), dwarf.clone()];
 
-    let logger = crate::logger::DebugLogger::new();
+    let mut logger = crate::logger::DebugLogger::new();
 
     let mut cb = CoreBuilder::new();
-    let core = cb
-        .load_warriors(&warriors)
-        .unwrap()
-        .log_with(Box::new(logger));
+    let core = cb.load_warriors(&warriors).unwrap();
 
     for _ in 0..=10 {
         let mut core = core.build().unwrap();
-        core.run();
+        core.log_with(&mut logger).run();
+    }
+}
+
+#[test]
+fn executions_are_observed() {
+    use crate::logger::{AccessKind::*, CellAccess, Logger};
+
+    #[derive(Debug, Default)]
+    struct Recorder(Vec<(usize, Vec<CellAccess>, Vec<usize>)>);
+
+    impl Logger for Recorder {
+        fn log(&mut self, _: &Core, event: &GameEvent) {
+            if let GameEvent::Executed(execution) = event {
+                self.0.push((
+                    execution.pc,
+                    execution.accesses.clone(),
+                    execution.queued.clone(),
+                ));
+            }
+        }
+
+        fn reset(&mut self) {
+            self.0.clear();
+        }
+    }
+
+    let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+    let mut recorder = Recorder::default();
+    let mut counter = Recorder::default();
+
+    let mut cb = CoreBuilder::new();
+    let builder = cb
+        .separation(Separation::Fixed(100))
+        .load_warriors(&[dwarf])
+        .unwrap();
+    let mut core = builder.build().unwrap();
+    core.log_with(&mut recorder).log_with(&mut counter);
+    for _ in 0..3 {
+        core.run_once();
+    }
+    drop(core);
+
+    let access = |address, kind| CellAccess { address, kind };
+    assert_eq!(
+        recorder.0,
+        vec![
+            (
+                1,
+                vec![
+                    access(1, Read),
+                    access(1, Read),
+                    access(0, Read),
+                    access(0, Write)
+                ],
+                vec![2]
+            ),
+            (
+                2,
+                vec![
+                    access(2, Read),
+                    access(2, Read),
+                    access(0, Read),
+                    access(4, Read),
+                    access(4, Write)
+                ],
+                vec![3]
+            ),
+            (
+                3,
+                vec![access(3, Read), access(1, Read), access(3, Read)],
+                vec![1]
+            ),
+        ]
+    );
+    assert_eq!(counter.0, recorder.0);
+
+    recorder.reset();
+    let mut core = builder.build().unwrap();
+    core.log_with(&mut recorder).run_once();
+    drop(core);
+    assert_eq!(recorder.0.len(), 1);
+}
+
+#[test]
+fn ownership_is_tracked() {
+    let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+
+    let mut cb = CoreBuilder::new();
+    cb.separation(Separation::Fixed(100))
+        .load_warriors(&[dwarf])
+        .unwrap();
+    let mut core = cb.build().unwrap();
+    core.run_once();
+    assert_eq!(core.owner(0), None);
+    assert_eq!(core.owners(), None);
+
+    let mut core = cb.track_ownership(true).build().unwrap();
+    assert_eq!(core.cells_owned_by(0), 4);
+    for _ in 0..3 {
+        core.run_once();
+    }
+    assert_eq!(
+        core.owner(4),
+        Some(Ownership {
+            warrior: 0,
+            cycle: 1
+        })
+    );
+    assert_eq!(core.owner(5), None);
+    assert_eq!(core.cells_owned_by(0), 5);
+    assert!(core.last_written().is_empty());
+
+    core.run_once();
+    assert_eq!(core.last_written(), &[0]);
+}
+
+#[test]
+fn spl_queues_its_target() {
+    let splitter = Warrior::parse(
+        "        ORG 0\n        SPL.B $2, $0\n        DAT.F #0, #0\n        DAT.F #7, #0\n        END\n",
+        0,
+    )
+    .unwrap();
+
+    let mut cb = CoreBuilder::new();
+    cb.separation(Separation::Fixed(100))
+        .load_warriors(&[splitter])
+        .unwrap();
+    let mut core = cb.build().unwrap();
+    core.run_once();
+
+    // The new process starts at the address SPL points to, not at that cell's A field.
+    assert_eq!(core.task_queues()[0].1, [1, 2]);
+}
+
+#[test]
+fn divide_by_zero_kills_the_process() {
+    let divider = Warrior::parse(
+        "        ORG 0\n        DIV.F $1, $2\n        DAT.F #0, #2\n        DAT.F #4, #6\n        END\n",
+        0,
+    )
+    .unwrap();
+
+    let mut cb = CoreBuilder::new();
+    cb.separation(Separation::Fixed(100))
+        .load_warriors(&[divider])
+        .unwrap();
+    let mut core = cb.build().unwrap();
+    core.run_once();
+
+    // The B field is still divided, even though dividing the A field killed the process.
+    assert_eq!(core.instructions()[2].to_string(), "DAT.F #4, #3");
+    assert!(core.task_queues()[0].1.is_empty());
+}
+
+#[test]
+fn process_deaths_are_attributed() {
+    use crate::logger::{DeathCause, Logger};
+
+    #[derive(Debug, Default)]
+    struct Deaths(Vec<(usize, DeathCause)>);
+
+    impl Logger for Deaths {
+        fn log(&mut self, _: &Core, event: &GameEvent) {
+            if let GameEvent::ProcessKilled(death) = event {
+                self.0.push((death.warrior.idx, death.cause));
+            }
+        }
     }
+
+    let warriors: Vec<Warrior> = [
+        "DAT.F #0, #0",
+        "DIV.AB #0, $0",
+        "JMP.B $1000, $0",
+        "JMP.B $-303, $0",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, instruction)| {
+        Warrior::parse(
+            &format!("        ORG 0\n        {}\n        END\n", instruction),
+            i,
+        )
+        .unwrap()
+    })
+    .collect();
+
+    let mut cb = CoreBuilder::new();
+    cb.separation(Separation::Fixed(100))
+        .load_warriors(&warriors)
+        .unwrap();
+
+    let mut deaths = Deaths::default();
+    let mut core = cb.build().unwrap();
+    core.log_with(&mut deaths).run();
+    drop(core);
+    assert_eq!(
+        deaths.0,
+        vec![
+            (0, DeathCause::Unknown),
+            (1, DeathCause::DivideByZero),
+            (2, DeathCause::Unknown),
+            (3, DeathCause::Unknown)
+        ]
+    );
+
+    let mut deaths = Deaths::default();
+    let mut core = cb.track_ownership(true).build().unwrap();
+    core.log_with(&mut deaths).run();
+    drop(core);
+    assert_eq!(
+        deaths.0,
+        vec![
+            (0, DeathCause::Warrior(0)),
+            (1, DeathCause::DivideByZero),
+            (2, DeathCause::InitialCore),
+            (3, DeathCause::Warrior(0))
+        ]
+    );
+}
+
+#[test]
+fn runs_until_stopped() {
+    let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+    let mut cb = CoreBuilder::new();
+    cb.cycles_before_tie(30)
+        .separation(Separation::Fixed(100))
+        .load_warriors(std::slice::from_ref(&dwarf))
+        .unwrap();
+
+    let mut core = cb.build().unwrap();
+    assert_eq!(core.run_until_write(4), None);
+    assert_eq!(core.cycle_count(), 2);
+    assert_eq!(core.last_written(), &[4]);
+    assert_eq!(core.run_for(10), None);
+    assert_eq!(core.cycle_count(), 12);
+    assert_eq!(core.outcome(), None);
+    assert_eq!(core.run_for(100), Some(MatchOutcome::Win(&dwarf)));
+    assert_eq!(core.cycle_count(), 30);
+    assert_eq!(core.run_for(100), Some(MatchOutcome::Win(&dwarf)));
+    assert_eq!(core.cycle_count(), 30);
+
+    // Stepping with run_once decides the outcome too, and does nothing once the match is over.
+    let mut core = cb.build().unwrap();
+    while let ExecutionOutcome::Continue(_) = core.run_once() {}
+    assert_eq!(core.outcome(), Some(&MatchOutcome::Win(&dwarf)));
+    assert!(matches!(core.run_once(), ExecutionOutcome::GameOver));
+    assert_eq!(core.run_for(10), Some(MatchOutcome::Win(&dwarf)));
+    assert_eq!(core.cycle_count(), 30);
+
+    let mut core = cb.build().unwrap();
+    assert_eq!(core.changes().count(), 29);
+    assert_eq!(core.outcome(), Some(&MatchOutcome::Win(&dwarf)));
+    assert_eq!(core.run(), MatchOutcome::Win(&dwarf));
+
+    // The stone bombs the waiting warrior, which ends the match with the kill.
+    let stone = Warrior::parse(include_str!("../../warriors/stone.red"), 0).unwrap();
+    let wait = Warrior::parse(include_str!("../../warriors/wait.red"), 1).unwrap();
+    cb.cycles_before_tie(2000)
+        .separation(Separation::Fixed(103 - stone.len()))
+        .load_warriors(&[stone.clone(), wait])
+        .unwrap();
+    let mut core = cb.build().unwrap();
+    assert_eq!(core.run_until_kill(), Some(MatchOutcome::Win(&stone)));
 }
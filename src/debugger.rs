@@ -0,0 +1,419 @@
+//! Stepping through a match an instruction at a time.
+//!
+//! A [`Debugger`](struct.Debugger.html) wraps a core and runs the
+//! [`DebugCommand`](enum.DebugCommand.html)s typed at its prompt, in the manner of pMARS's cdb:
+//! stepping, continuing to a breakpoint or watchpoint, showing the process queues, listing the
+//! code around a process, editing cells and choosing which warrior runs next.
+use crate::{
+    core::{Core, CoreChange},
+    error::DebugError,
+    parser::instruction::{opcode, Opcode},
+    warrior::Warrior,
+};
+use nom::combinator::all_consuming;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The number of cells shown before and after the address being listed.
+const LIST_CONTEXT: usize = 4;
+
+/// The number of addresses shown for each warrior's process queue.
+const QUEUE_LIMIT: usize = 16;
+
+/// Something that stops the debugger when it is continuing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stop {
+    /// Stop before the instruction at this address is executed.
+    Address(usize),
+    /// Stop before an instruction with this opcode is executed.
+    Opcode(Opcode),
+    /// Stop after this cell is written to.
+    Write(usize),
+}
+
+/// A command given to the debugger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Execute this many instructions, stopping early at a breakpoint or watchpoint.
+    Step(usize),
+    /// Execute instructions until a breakpoint or watchpoint, or until the match is over.
+    Continue,
+    /// Add a breakpoint or watchpoint.
+    Break(Stop),
+    /// Remove the breakpoint or watchpoint with this number.
+    Delete(usize),
+    /// Show the breakpoints and watchpoints.
+    Breakpoints,
+    /// Show each warrior's process queue.
+    Queues,
+    /// Show the cells around an address, or around the next process if none is given.
+    List(Option<usize>),
+    /// Overwrite the cell at an address with an instruction, given as Redcode.
+    Edit(usize, String),
+    /// Make the warrior with this index the next to execute an instruction.
+    Switch(usize),
+    Help,
+    Quit,
+}
+
+impl FromStr for DebugCommand {
+    type Err = DebugError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, rest) = match s.split_once(char::is_whitespace) {
+            Some((name, rest)) => (name, rest.trim()),
+            None => (s, ""),
+        };
+        let number = |what: &'static str| {
+            rest.parse::<usize>()
+                .map_err(|_| DebugError::BadArgument(name.to_owned(), what))
+        };
+
+        Ok(match name.to_lowercase().as_str() {
+            // An empty line steps, as in cdb.
+            "" => DebugCommand::Step(1),
+            "s" | "step" if rest.is_empty() => DebugCommand::Step(1),
+            "s" | "step" => DebugCommand::Step(number("a number of instructions")?),
+            "c" | "continue" => DebugCommand::Continue,
+            "b" | "break" => match rest.parse() {
+                Ok(address) => DebugCommand::Break(Stop::Address(address)),
+                Err(_) => match all_consuming(opcode)(rest) {
+                    Ok((_, opcode)) => DebugCommand::Break(Stop::Opcode(opcode)),
+                    Err(_) => {
+                        return Err(DebugError::BadArgument(
+                            name.to_owned(),
+                            "an address or an opcode",
+                        ))
+                    }
+                },
+            },
+            "w" | "watch" => DebugCommand::Break(Stop::Write(number("an address")?)),
+            "d" | "delete" => DebugCommand::Delete(number("a breakpoint number")?),
+            "i" | "info" | "breakpoints" => DebugCommand::Breakpoints,
+            "p" | "queues" => DebugCommand::Queues,
+            "l" | "list" if rest.is_empty() => DebugCommand::List(None),
+            "l" | "list" => DebugCommand::List(Some(number("an address")?)),
+            "e" | "edit" => {
+                let (address, instruction) = rest
+                    .split_once(char::is_whitespace)
+                    .and_then(|(address, instruction)| {
+                        Some((address.parse().ok()?, instruction.trim().to_owned()))
+                    })
+                    .ok_or_else(|| {
+                        DebugError::BadArgument(name.to_owned(), "an address and an instruction")
+                    })?;
+                DebugCommand::Edit(address, instruction)
+            }
+            "switch" => DebugCommand::Switch(number("a warrior index")?),
+            "h" | "help" | "?" => DebugCommand::Help,
+            "q" | "quit" | "exit" => DebugCommand::Quit,
+            _ => return Err(DebugError::UnknownCommand(name.to_owned())),
+        })
+    }
+}
+
+const HELP: &str = "\
+step [n]           execute n instructions, or one (an empty line also steps)
+continue           execute until a breakpoint, a watchpoint or the end of the match
+break <address>    stop before the instruction at an address is executed
+break <opcode>     stop before an instruction with an opcode is executed
+watch <address>    stop after a cell is written to
+delete <n>         remove breakpoint or watchpoint n
+breakpoints        show the breakpoints and watchpoints
+queues             show each warrior's process queue
+list [address]     show the cells around an address, or around the next process
+edit <address> <instruction>
+                   overwrite a cell with an instruction
+switch <warrior>   make a warrior the next to execute an instruction
+quit               leave the debugger";
+
+/// Runs a core under the control of `DebugCommand`s.
+#[derive(Debug)]
+pub struct Debugger<'a, 'l> {
+    core: Core<'a, 'l>,
+    /// The breakpoints and watchpoints, numbered by position. Deleted ones are left as `None`
+    /// so the rest keep their numbers.
+    stops: Vec<Option<Stop>>,
+    /// The label of each labelled cell.
+    labels: HashMap<usize, String>,
+}
+
+impl<'a, 'l> Debugger<'a, 'l> {
+    pub fn new(core: Core<'a, 'l>) -> Self {
+        Self {
+            core,
+            stops: Vec::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Sets the labels of the warrior loaded at position `warrior`, as found by
+    /// [`Warrior::labels`](../warrior/struct.Warrior.html#method.labels), so they are shown in
+    /// listings.
+    pub fn labels(&mut self, warrior: usize, labels: &[(String, usize)]) -> &mut Self {
+        let size = self.core.instructions().len();
+        if let Some(&start) = self.core.loaded_at().get(warrior) {
+            for (label, position) in labels {
+                self.labels
+                    .entry((start + position) % size)
+                    .or_insert_with(|| label.clone());
+            }
+        }
+
+        self
+    }
+
+    pub fn core(&self) -> &Core<'a, 'l> {
+        &self.core
+    }
+
+    /// Runs `command`, returning what it prints.
+    pub fn execute(&mut self, command: &DebugCommand) -> Result<String, DebugError> {
+        let size = self.core.instructions().len();
+
+        Ok(match command {
+            DebugCommand::Step(n) => self.run(Some(*n)),
+            DebugCommand::Continue => self.run(None),
+            DebugCommand::Break(stop) => {
+                let stop = match stop {
+                    Stop::Address(address) => Stop::Address(address % size),
+                    Stop::Write(address) => Stop::Write(address % size),
+                    Stop::Opcode(_) => stop.clone(),
+                };
+                let description = describe(&stop);
+                self.stops.push(Some(stop));
+                format!("{} {}", self.stops.len(), description)
+            }
+            DebugCommand::Delete(n) => match self.stops.get_mut(n.wrapping_sub(1)) {
+                Some(stop @ Some(_)) => {
+                    *stop = None;
+                    format!("Deleted {}", n)
+                }
+                _ => return Err(DebugError::NoBreakpoint(*n)),
+            },
+            DebugCommand::Breakpoints => {
+                let mut out = String::new();
+                for (n, stop) in self.stops.iter().enumerate() {
+                    if let Some(stop) = stop {
+                        writeln!(out, "{} {}", n + 1, describe(stop)).unwrap();
+                    }
+                }
+                if out.is_empty() {
+                    out.push_str("No breakpoints or watchpoints");
+                }
+                out.trim_end().to_owned()
+            }
+            DebugCommand::Queues => self.queues(),
+            DebugCommand::List(address) => {
+                let address = match address {
+                    Some(address) => address % size,
+                    None => self.core.next_process().map_or(0, |(_, pc)| pc),
+                };
+                self.list(address)
+            }
+            DebugCommand::Edit(address, instruction) => {
+                // The parser expects a warrior to finish with END.
+                let warrior = Warrior::parse(&format!("{}\nend\n", instruction), 0)
+                    .map_err(DebugError::Instruction)?;
+                if warrior.len() != 1 {
+                    return Err(DebugError::NotOneInstruction(warrior.len()));
+                }
+                self.core
+                    .set_instruction(*address, &warrior.instructions()[0]);
+                self.line(address % size)
+            }
+            DebugCommand::Switch(warrior) => {
+                if !self.core.switch_to(*warrior) {
+                    return Err(DebugError::NoWarrior(*warrior));
+                }
+                self.status()
+            }
+            DebugCommand::Help => HELP.to_owned(),
+            DebugCommand::Quit => String::new(),
+        })
+    }
+
+    /// Executes instructions until `limit` have been executed, if there is a limit, until a
+    /// breakpoint or watchpoint is reached, or until the match is over.
+    fn run(&mut self, limit: Option<usize>) -> String {
+        if limit == Some(0) {
+            return self.status();
+        }
+
+        let stops = &self.stops;
+        let mut executed = 0;
+        let mut reached = None;
+        self.core.watching_writes(|core| {
+            core.run_until(|core, change| {
+                if let CoreChange::WarriorPlayed { .. } = change {
+                    executed += 1;
+                }
+                reached = stops
+                    .iter()
+                    .enumerate()
+                    .find(|(_, stop)| stop.as_ref().is_some_and(|stop| reached_stop(core, stop)))
+                    .map(|(n, _)| n + 1);
+
+                reached.is_some() || limit.is_some_and(|limit| executed >= limit)
+            })
+        });
+
+        match reached.and_then(|n| Some((n, self.stops[n - 1].as_ref()?))) {
+            Some((n, stop)) => format!("Stopped at {} {}\n{}", n, describe(stop), self.status()),
+            None => self.status(),
+        }
+    }
+
+    /// The cycle count and the instruction that will be executed next, or the outcome of the
+    /// match if it is over.
+    fn status(&self) -> String {
+        let cycle = self.core.cycle_count();
+        if let Some(outcome) = self.core.outcome() {
+            return format!("Cycle {}: {}", cycle, outcome);
+        }
+
+        match self.core.next_process() {
+            Some((warrior, pc)) => format!(
+                "Cycle {}: warrior {} ({}) next executes\n{}",
+                cycle,
+                warrior.idx,
+                warrior.metadata.display_name(),
+                self.line(pc)
+            ),
+            None => format!("Cycle {}", cycle),
+        }
+    }
+
+    fn queues(&self) -> String {
+        let mut out = String::new();
+        for (warrior, queue) in self.core.task_queues() {
+            let mut addresses: Vec<String> = queue
+                .iter()
+                .take(QUEUE_LIMIT)
+                .map(|address| address.to_string())
+                .collect();
+            if queue.len() > QUEUE_LIMIT {
+                addresses.push(String::from("..."));
+            }
+            writeln!(
+                out,
+                "Warrior {} ({}), {} process{}: {}",
+                warrior.idx,
+                warrior.metadata.display_name(),
+                queue.len(),
+                if queue.len() == 1 { "" } else { "es" },
+                addresses.join(" ")
+            )
+            .unwrap();
+        }
+
+        out.trim_end().to_owned()
+    }
+
+    /// The cells around `address`, with the next process marked with `>` and the rest of the
+    /// processes with `*`.
+    fn list(&self, address: usize) -> String {
+        let size = self.core.instructions().len();
+        let next = self.core.next_process().map(|(_, pc)| pc);
+        let context = LIST_CONTEXT.min(size / 2);
+
+        let mut out = String::new();
+        for i in 0..(context * 2 + 1).min(size) {
+            let cell = (address + size - context + i) % size;
+            let marker = if Some(cell) == next {
+                '>'
+            } else if self
+                .core
+                .task_queues()
+                .iter()
+                .any(|(_, queue)| queue.contains(&cell))
+            {
+                '*'
+            } else {
+                ' '
+            };
+            writeln!(out, "{} {}", marker, self.line(cell)).unwrap();
+        }
+
+        out.trim_end().to_owned()
+    }
+
+    /// A cell's address, label and instruction.
+    fn line(&self, address: usize) -> String {
+        format!(
+            "{:>6} {:<10} {}",
+            address,
+            self.labels.get(&address).map_or("", String::as_str),
+            self.core.instructions()[address]
+        )
+    }
+}
+
+fn reached_stop(core: &Core, stop: &Stop) -> bool {
+    match stop {
+        Stop::Write(address) => core.last_written().contains(address),
+        Stop::Address(address) => core.next_process().is_some_and(|(_, pc)| pc == *address),
+        Stop::Opcode(opcode) => core
+            .next_process()
+            .is_some_and(|(_, pc)| core.instructions()[pc].opcode() == opcode),
+    }
+}
+
+fn describe(stop: &Stop) -> String {
+    match stop {
+        Stop::Address(address) => format!("breakpoint at {}", address),
+        Stop::Opcode(opcode) => format!("breakpoint on {}", opcode),
+        Stop::Write(address) => format!("watchpoint on {}", address),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{CoreBuilder, Separation};
+
+    #[test]
+    fn debugs_a_round() {
+        let source = include_str!("../warriors/dwarf.red");
+        let dwarf = Warrior::parse(source, 0).unwrap();
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(100)
+            .separation(Separation::Fixed(100))
+            .load_warriors(&[dwarf])
+            .unwrap();
+        let mut debugger = Debugger::new(builder.build().unwrap());
+        debugger.labels(0, &Warrior::labels(source).unwrap());
+        let mut run = |line: &str| debugger.execute(&line.parse().unwrap()).unwrap();
+
+        assert!(run("list").contains(">      1 start      ADD.AB #4, $7999"));
+        assert_eq!(run("watch 8"), "1 watchpoint on 8");
+        assert_eq!(run("break mov"), "2 breakpoint on MOV");
+        assert!(run("continue").starts_with("Stopped at 2 breakpoint on MOV\nCycle 1:"));
+        assert_eq!(run("delete 2"), "Deleted 2");
+        assert!(run("c").starts_with("Stopped at 1 watchpoint on 8\nCycle 5:"));
+        assert!(run("step 3").starts_with("Cycle 8:"));
+        assert_eq!(
+            run("queues"),
+            "Warrior 0 (Dwarf by A. K. Dewdney), 1 process: 3"
+        );
+
+        assert!(run("edit 2 jmp 0").ends_with("JMP.B $0, $0"));
+        assert_eq!(
+            run("s 10"),
+            "Cycle 18: warrior 0 (Dwarf by A. K. Dewdney) next executes\n     2            JMP.B $0, $0"
+        );
+        assert!(run("c").starts_with("Cycle 100: Match won by"));
+
+        assert!(matches!(
+            "fly".parse::<DebugCommand>(),
+            Err(DebugError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            "break here".parse::<DebugCommand>(),
+            Err(DebugError::BadArgument(..))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
+use crate::replay::ReplayOutcome;
 use thiserror::Error;
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("Couldn't parse warrior")]
@@ -7,6 +9,8 @@ pub enum ParseError {
     Replace,
     #[error("Warrior incomplete")]
     Incomplete,
+    #[error("Override given for {0}, which the warrior doesn't define")]
+    UnknownOverride(String),
 }
 
 #[derive(Error, Debug)]
@@ -39,6 +43,14 @@ pub enum CoreError {
     EmptyWarrior(String),
     #[error("Encountered a warrior of length {0} greater than max length {1}: {2}")]
     WarriorTooLong(usize, usize, String),
+    #[error("Unknown core preset: {0}")]
+    UnknownPreset(String),
+    #[error("No warriors loaded")]
+    NoWarriors,
+    #[error("Expected a position for each of {0} warriors, found {1}")]
+    Positions(usize, usize),
+    #[error("Snapshot doesn't fit the core: {0}")]
+    SnapshotMismatch(&'static str),
 }
 
 #[derive(Error, Debug)]
@@ -48,3 +60,77 @@ pub enum Error {
     #[error("Error evaluating warrior: {0}")]
     Evaluate(EvaluateError),
 }
+
+#[derive(Error, Debug)]
+pub enum HillError {
+    #[error("Couldn't read or write hill: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Couldn't parse hill: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("Couldn't load warrior {0}: {1}")]
+    Warrior(String, Error),
+    #[error("Error running challenge: {0}")]
+    Core(#[from] CoreError),
+}
+
+#[derive(Error, Debug)]
+pub enum OptimiseError {
+    #[error("Couldn't parse tuning marker for {0}: {1}")]
+    BadMarker(String, String),
+    #[error("Warrior has no EQU definitions marked with ;tune")]
+    NoTunables,
+    #[error("Grid search would try {0} candidates, more than the maximum of {1}")]
+    TooManyCandidates(usize, usize),
+    #[error("Couldn't load warrior with {0}: {1}")]
+    Warrior(String, Error),
+    #[error("Error running matches: {0}")]
+    Core(#[from] CoreError),
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("Couldn't read or write replay: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Couldn't parse replay: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("Couldn't load warrior {0}: {1}")]
+    Warrior(String, Error),
+    #[error("Couldn't build core: {0}")]
+    Core(#[from] CoreError),
+    #[error("Warriors started at {1:?}, but were recorded starting at {0:?}")]
+    Positions(Vec<usize>, Vec<usize>),
+    #[error("Round was {1}, but was recorded as {0}")]
+    Outcome(ReplayOutcome, ReplayOutcome),
+}
+
+#[derive(Error, Debug)]
+pub enum RenderError {
+    #[error("Couldn't write image: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Couldn't encode PNG: {0}")]
+    Png(#[from] png::EncodingError),
+    #[error("Couldn't encode GIF: {0}")]
+    Gif(#[from] gif::EncodingError),
+    #[error("Animation has no frames")]
+    Empty,
+    #[error("Image of {0}x{1} pixels is too large for a GIF")]
+    TooLarge(usize, usize),
+    #[error("Animation uses {0} colours, more than a GIF can hold")]
+    TooManyColours(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum DebugError {
+    #[error("Unknown command {0}, try help")]
+    UnknownCommand(String),
+    #[error("Expected {1} for {0}")]
+    BadArgument(String, &'static str),
+    #[error("Couldn't parse instruction: {0}")]
+    Instruction(Error),
+    #[error("Expected a single instruction, found {0}")]
+    NotOneInstruction(usize),
+    #[error("No warrior {0} is still alive")]
+    NoWarrior(usize),
+    #[error("No breakpoint or watchpoint {0}")]
+    NoBreakpoint(usize),
+}
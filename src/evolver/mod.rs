@@ -0,0 +1,380 @@
+//! Evolving warriors with a genetic algorithm.
+//!
+//! An [`Evolver`](struct.Evolver.html) keeps a [`Population`](struct.Population.html) of
+//! warriors. Each generation, every warrior's fitness is measured by fighting it, either against
+//! a fixed benchmark set or against the rest of the population, and the next generation is bred
+//! from the fittest warriors by [`crossover`](fn.crossover.html) and
+//! [`Mutation`](enum.Mutation.html)s.
+mod mutation;
+pub use mutation::*;
+
+use crate::{
+    core::CoreBuilder, error::CoreError, runner::Runner, score::Standard, tournament::Tournament,
+    warrior::Warrior,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::cmp::Ordering;
+use std::io;
+use std::path::Path;
+
+/// How the fitness of each warrior in a population is measured.
+#[derive(Debug, Clone)]
+pub enum Fitness {
+    /// A warrior's fitness is its average score against each of these warriors.
+    Benchmark(Vec<Warrior>),
+    /// A warrior's fitness is its average score against every other warrior in the population.
+    RoundRobin,
+}
+
+/// A warrior in a population, along with its fitness.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    /// The warrior.
+    pub warrior: Warrior,
+    /// The warrior's fitness: its average score, in points per 100 rounds, or `None` if it hasn't
+    /// been evaluated yet.
+    pub fitness: Option<f64>,
+}
+
+impl Member {
+    /// Orders members by fitness, with unevaluated members below every evaluated one.
+    fn cmp_fitness(&self, other: &Self) -> Ordering {
+        match (self.fitness, other.fitness) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+/// A generation of warriors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Population {
+    generation: usize,
+    members: Vec<Member>,
+}
+
+impl Population {
+    /// Creates the first generation from `warriors`, none of which have been evaluated.
+    pub fn new(warriors: Vec<Warrior>) -> Self {
+        Self {
+            generation: 0,
+            members: warriors
+                .into_iter()
+                .map(|warrior| Member {
+                    warrior,
+                    fitness: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// The number of generations bred before this one.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// The warriors in the population.
+    pub fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    /// The fittest evaluated warrior in the population.
+    pub fn fittest(&self) -> Option<&Member> {
+        self.members
+            .iter()
+            .filter(|m| m.fitness.is_some())
+            .max_by(|a, b| a.cmp_fitness(b))
+    }
+
+    /// Writes every warrior in the population to `directory` as a `.red` file, named by
+    /// generation and position, with its fitness noted in its strategy.
+    pub fn checkpoint(&self, directory: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        for (i, member) in self.members.iter().enumerate() {
+            let mut warrior = member.warrior.clone();
+            warrior
+                .metadata
+                .set_name(Some(format!("gen{:04}-{:03}", self.generation, i)));
+            if let Some(fitness) = member.fitness {
+                warrior
+                    .metadata
+                    .set_strategy(Some(format!("Fitness {:.1}", fitness)));
+            }
+
+            std::fs::write(
+                directory.join(format!("gen{:04}-{:03}.red", self.generation, i)),
+                warrior.to_redcode(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Breeds warriors with a genetic algorithm.
+#[derive(Debug, Clone)]
+pub struct Evolver {
+    builder: CoreBuilder,
+    fitness: Fitness,
+    rounds: usize,
+    elite: usize,
+    crossover_rate: f64,
+    mutations: usize,
+    selection_size: usize,
+    runner: Runner,
+}
+
+impl Evolver {
+    /// Creates a new evolver, fighting every round with the settings in `builder` and measuring
+    /// fitness with `fitness`. New warriors are kept within `builder`'s instruction limit.
+    pub fn new(builder: CoreBuilder, fitness: Fitness) -> Self {
+        Self {
+            builder,
+            fitness,
+            rounds: 20,
+            elite: 2,
+            crossover_rate: 0.3,
+            mutations: 2,
+            selection_size: 3,
+            runner: Runner::new(),
+        }
+    }
+
+    /// Sets the number of rounds fought in each pairing when measuring fitness.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the number of fittest warriors copied unchanged into the next generation.
+    pub fn elite(&mut self, elite: usize) -> &mut Self {
+        self.elite = elite;
+        self
+    }
+
+    /// Sets the probability that a new warrior is bred from two parents rather than one.
+    pub fn crossover_rate(&mut self, crossover_rate: f64) -> &mut Self {
+        self.crossover_rate = crossover_rate;
+        self
+    }
+
+    /// Sets the maximum number of mutations applied to each new warrior. Each new warrior gets
+    /// between one and this many.
+    pub fn mutations(&mut self, mutations: usize) -> &mut Self {
+        self.mutations = mutations;
+        self
+    }
+
+    /// Sets the number of warriors drawn at random when selecting a parent, of which the
+    /// fittest is chosen.
+    pub fn selection_size(&mut self, selection_size: usize) -> &mut Self {
+        self.selection_size = selection_size;
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Creates a first generation of `size` warriors. The warriors in `seeds` are included as
+    /// they are, and the rest are mutated copies of them, or random warriors if there are no
+    /// seeds.
+    pub fn initial_population<R: Rng>(
+        &self,
+        seeds: &[Warrior],
+        size: usize,
+        rng: &mut R,
+    ) -> Population {
+        let mut warriors: Vec<Warrior> = seeds.iter().take(size).cloned().collect();
+        while warriors.len() < size {
+            let warrior = match seeds.choose(rng) {
+                Some(seed) => self.mutate(seed.clone(), rng),
+                None => random_warrior(
+                    self.builder.instruction_limit.min(10),
+                    self.builder.core_size,
+                    rng,
+                ),
+            };
+            warriors.push(warrior);
+        }
+
+        Population::new(warriors)
+    }
+
+    /// Measures the fitness of every warrior in `population`.
+    pub fn evaluate(&self, population: &mut Population) -> Result<(), CoreError> {
+        let mut warriors: Vec<Warrior> = population
+            .members
+            .iter()
+            .map(|m| m.warrior.clone())
+            .collect();
+        let size = warriors.len();
+
+        let pairings: Vec<(usize, usize)> = match self.fitness {
+            Fitness::Benchmark(ref benchmark) => {
+                warriors.extend_from_slice(benchmark);
+                (0..size)
+                    .flat_map(|a| (size..warriors.len()).map(move |b| (a, b)))
+                    .collect()
+            }
+            Fitness::RoundRobin => Tournament::new().pairings(size),
+        };
+
+        let results = Tournament::new()
+            .rounds(self.rounds)
+            .runner(self.runner.clone())
+            .run_pairings(&self.builder, &warriors, &pairings, &Standard::new())?;
+
+        for (i, member) in population.members.iter_mut().enumerate() {
+            member.fitness = Some(results.total(i));
+        }
+
+        Ok(())
+    }
+
+    /// Breeds the next generation from an evaluated `population`, keeping its size.
+    pub fn next_generation<R: Rng>(&self, population: &Population, rng: &mut R) -> Population {
+        let mut ranked: Vec<&Member> = population.members.iter().collect();
+        ranked.sort_by(|a, b| b.cmp_fitness(a));
+
+        let mut warriors: Vec<Warrior> = ranked
+            .iter()
+            .take(self.elite)
+            .map(|m| m.warrior.clone())
+            .collect();
+        while warriors.len() < population.members.len() {
+            let parent = self.select(population, rng);
+            let child = if rng.gen_bool(self.crossover_rate) {
+                let other = self.select(population, rng);
+                crossover(parent, other, self.builder.instruction_limit, rng)
+            } else {
+                parent.clone()
+            };
+            warriors.push(self.mutate(child, rng));
+        }
+
+        Population {
+            generation: population.generation + 1,
+            members: warriors
+                .into_iter()
+                .map(|warrior| Member {
+                    warrior,
+                    fitness: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Evaluates `population`, then breeds and evaluates generations from it until `generations`
+    /// have been bred, calling `on_generation` with each evaluated generation, including the
+    /// first. Returns the final generation.
+    pub fn evolve<R, F>(
+        &self,
+        mut population: Population,
+        generations: usize,
+        rng: &mut R,
+        mut on_generation: F,
+    ) -> Result<Population, CoreError>
+    where
+        R: Rng,
+        F: FnMut(&Population),
+    {
+        self.evaluate(&mut population)?;
+        on_generation(&population);
+
+        for _ in 0..generations {
+            population = self.next_generation(&population, rng);
+            self.evaluate(&mut population)?;
+            on_generation(&population);
+        }
+
+        Ok(population)
+    }
+
+    fn select<'p, R: Rng>(&self, population: &'p Population, rng: &mut R) -> &'p Warrior {
+        &population
+            .members
+            .choose_multiple(rng, self.selection_size.max(1))
+            .max_by(|a, b| a.cmp_fitness(b))
+            .unwrap()
+            .warrior
+    }
+
+    fn mutate<R: Rng>(&self, mut warrior: Warrior, rng: &mut R) -> Warrior {
+        for _ in 0..rng.gen_range(1, self.mutations.max(1) + 1) {
+            Mutation::ALL.choose(rng).unwrap().apply(
+                &mut warrior,
+                self.builder.instruction_limit,
+                self.builder.core_size,
+                rng,
+            );
+        }
+
+        warrior
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn mutations_respect_instruction_limit() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut warrior = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+
+        for _ in 0..1000 {
+            let mutation = *Mutation::ALL.choose(&mut rng).unwrap();
+            mutation.apply(&mut warrior, 6, 8000, &mut rng);
+
+            assert!(!warrior.is_empty() && warrior.len() <= 6);
+            assert!(warrior.starts_at_line() < warrior.len());
+            for instruction in warrior.instructions() {
+                assert!(instruction.addr_a.abs() <= 4000 && instruction.addr_b.abs() <= 4000);
+            }
+        }
+
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        for _ in 0..100 {
+            let child = crossover(&warrior, &imp, 4, &mut rng);
+            assert!(!child.is_empty() && child.len() <= 4);
+            assert!(child.starts_at_line() < child.len());
+        }
+    }
+
+    #[test]
+    fn evolves_and_checkpoints() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder.preset(crate::core::Preset::Nano);
+        let mut evolver = Evolver::new(builder, Fitness::Benchmark(vec![imp]));
+        evolver.rounds(2);
+
+        let population = evolver.initial_population(&[dwarf], 6, &mut rng);
+        assert_eq!(population.members().len(), 6);
+
+        let mut generations = 0;
+        let population = evolver
+            .evolve(population, 2, &mut rng, |_| generations += 1)
+            .unwrap();
+        assert_eq!(generations, 3);
+        assert_eq!(population.generation(), 2);
+        assert!(population.members().iter().all(|m| m.fitness.is_some()));
+
+        let directory = std::env::temp_dir().join(format!("mars-evolver-{}", std::process::id()));
+        population.checkpoint(&directory).unwrap();
+        let saved = std::fs::read_to_string(directory.join("gen0002-000.red")).unwrap();
+        let warrior = Warrior::parse(&saved, 0).unwrap();
+        assert_eq!(
+            warrior.instructions(),
+            population.members()[0].warrior.instructions()
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
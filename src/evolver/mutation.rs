@@ -0,0 +1,219 @@
+use crate::{
+    parser::instruction::{AddressMode, Modifier, Opcode},
+    warrior::{Instruction, Warrior},
+};
+use rand::{seq::SliceRandom, Rng};
+
+const OPCODES: [Opcode; 16] = [
+    Opcode::Dat,
+    Opcode::Mov,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Div,
+    Opcode::Mod,
+    Opcode::Jmp,
+    Opcode::Jmz,
+    Opcode::Jmn,
+    Opcode::Djn,
+    Opcode::Slt,
+    Opcode::Seq,
+    Opcode::Sne,
+    Opcode::Spl,
+    Opcode::Nop,
+];
+
+const MODIFIERS: [Modifier; 7] = [
+    Modifier::A,
+    Modifier::B,
+    Modifier::AB,
+    Modifier::BA,
+    Modifier::F,
+    Modifier::X,
+    Modifier::I,
+];
+
+const MODES: [AddressMode; 8] = [
+    AddressMode::Immediate,
+    AddressMode::Direct,
+    AddressMode::AFieldIndirect,
+    AddressMode::BFieldIndirect,
+    AddressMode::AFieldPredecrementIndirect,
+    AddressMode::BFieldPredecrementIndirect,
+    AddressMode::AFieldPostincrementIndirect,
+    AddressMode::BFieldPostincrementIndirect,
+];
+
+/// A change that can be made to a warrior's code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// Replace an instruction's opcode, keeping its modifier.
+    Opcode,
+    /// Replace an instruction's modifier.
+    Modifier,
+    /// Replace the addressing mode of one of an instruction's fields.
+    Mode,
+    /// Change the value of one of an instruction's fields, either by a small step or to a new
+    /// random value.
+    Field,
+    /// Insert a random instruction.
+    Insert,
+    /// Delete an instruction.
+    Delete,
+    /// Insert a copy of an instruction directly after it.
+    Duplicate,
+}
+
+impl Mutation {
+    /// Every kind of mutation.
+    pub const ALL: [Mutation; 7] = [
+        Mutation::Opcode,
+        Mutation::Modifier,
+        Mutation::Mode,
+        Mutation::Field,
+        Mutation::Insert,
+        Mutation::Delete,
+        Mutation::Duplicate,
+    ];
+
+    /// Applies the mutation to `warrior` at a random line. Mutations that would take the warrior
+    /// outside of 1 to `instruction_limit` lines leave it unchanged. Field values are kept between
+    /// `-core_size / 2` and `core_size / 2`.
+    pub fn apply<R: Rng>(
+        self,
+        warrior: &mut Warrior,
+        instruction_limit: usize,
+        core_size: usize,
+        rng: &mut R,
+    ) {
+        let len = warrior.instructions.len();
+        if len == 0 {
+            if self == Mutation::Insert && instruction_limit > 0 {
+                warrior
+                    .instructions
+                    .push(random_instruction(core_size, rng));
+            }
+            return;
+        }
+
+        let line = rng.gen_range(0, len);
+        let instruction = &mut warrior.instructions[line];
+        match self {
+            Mutation::Opcode => instruction.opcode = OPCODES.choose(rng).unwrap().clone(),
+            Mutation::Modifier => instruction.modifier = MODIFIERS.choose(rng).unwrap().clone(),
+            Mutation::Mode => {
+                let mode = *MODES.choose(rng).unwrap();
+                if rng.gen() {
+                    instruction.mode_a = mode;
+                } else {
+                    instruction.mode_b = mode;
+                }
+            }
+            Mutation::Field => {
+                let field = if rng.gen() {
+                    &mut instruction.addr_a
+                } else {
+                    &mut instruction.addr_b
+                };
+                *field = if rng.gen_bool(0.75) {
+                    wrap(*field + rng.gen_range(-8, 9), core_size)
+                } else {
+                    random_field(core_size, rng)
+                };
+            }
+            Mutation::Insert if len < instruction_limit => {
+                let position = rng.gen_range(0, len + 1);
+                warrior
+                    .instructions
+                    .insert(position, random_instruction(core_size, rng));
+                if position <= warrior.starts_at_line {
+                    warrior.starts_at_line += 1;
+                }
+            }
+            Mutation::Delete if len > 1 => {
+                warrior.instructions.remove(line);
+                if line < warrior.starts_at_line {
+                    warrior.starts_at_line -= 1;
+                }
+                warrior.starts_at_line = warrior.starts_at_line.min(len - 2);
+            }
+            Mutation::Duplicate if len < instruction_limit => {
+                let copy = warrior.instructions[line].clone();
+                warrior.instructions.insert(line + 1, copy);
+                if line < warrior.starts_at_line {
+                    warrior.starts_at_line += 1;
+                }
+            }
+            Mutation::Insert | Mutation::Delete | Mutation::Duplicate => {}
+        }
+    }
+}
+
+/// Creates a child warrior from the first part of `a` and the last part of `b`, cut at random
+/// lines, keeping `a`'s starting line and metadata. The child is truncated to
+/// `instruction_limit` lines.
+pub fn crossover<R: Rng>(
+    a: &Warrior,
+    b: &Warrior,
+    instruction_limit: usize,
+    rng: &mut R,
+) -> Warrior {
+    let cut_a = rng.gen_range(0, a.instructions.len() + 1);
+    let cut_b = rng.gen_range(0, b.instructions.len() + 1);
+
+    let mut child = a.clone();
+    child.instructions.truncate(cut_a);
+    child
+        .instructions
+        .extend_from_slice(&b.instructions[cut_b..]);
+    if child.instructions.is_empty() {
+        child.instructions = a.instructions.clone();
+    }
+    child.instructions.truncate(instruction_limit.max(1));
+    child.starts_at_line = child.starts_at_line.min(child.instructions.len() - 1);
+
+    child
+}
+
+/// Creates a warrior of between one and `length` random instructions.
+pub fn random_warrior<R: Rng>(length: usize, core_size: usize, rng: &mut R) -> Warrior {
+    let length = rng.gen_range(1, length.max(1) + 1);
+    let instructions = (0..length)
+        .map(|_| random_instruction(core_size, rng))
+        .collect();
+
+    Warrior {
+        metadata: Default::default(),
+        idx: 0,
+        instructions,
+        starts_at_line: rng.gen_range(0, length),
+    }
+}
+
+fn random_instruction<R: Rng>(core_size: usize, rng: &mut R) -> Instruction {
+    let opcode = OPCODES.choose(rng).unwrap().clone();
+    Instruction::new(
+        opcode.clone(),
+        opcode.default_modifier(),
+        *MODES.choose(rng).unwrap(),
+        random_field(core_size, rng),
+        *MODES.choose(rng).unwrap(),
+        random_field(core_size, rng),
+    )
+}
+
+fn random_field<R: Rng>(core_size: usize, rng: &mut R) -> i64 {
+    // Most useful field values are small, so they're chosen far more often.
+    if rng.gen_bool(0.5) {
+        rng.gen_range(-16, 17)
+    } else {
+        let half = (core_size / 2) as i64;
+        rng.gen_range(-half, half + 1)
+    }
+}
+
+fn wrap(value: i64, core_size: usize) -> i64 {
+    let size = core_size as i64;
+    let half = size / 2;
+    (value + half).rem_euclid(size) - half
+}
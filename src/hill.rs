@@ -0,0 +1,308 @@
+//! A local King-of-the-Hill, which keeps a ranked list of warriors and their results against each
+//! other, and can be saved to and loaded from disk.
+//!
+//! When a new warrior challenges the hill it fights every warrior already on it, and the hill is
+//! re-ranked by each warrior's average score against the others. If the hill is then over
+//! capacity, the last-placed warrior is pushed off.
+use crate::{
+    core::{CoreBuilder, Preset},
+    error::HillError,
+    runner::Runner,
+    score::Standard,
+    tournament::{Tournament, TournamentResults},
+    warrior::Warrior,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// A King-of-the-Hill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hill {
+    preset: Preset,
+    size: usize,
+    rounds: usize,
+    next_id: usize,
+    warriors: Vec<HillWarrior>,
+    results: Vec<PairResult>,
+}
+
+/// A warrior on the hill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HillWarrior {
+    id: usize,
+    name: String,
+    author: String,
+    source: String,
+    age: usize,
+}
+
+impl HillWarrior {
+    /// The warrior's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The warrior's author.
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// The warrior's Redcode source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The number of challenges the warrior has survived since joining the hill.
+    pub fn age(&self) -> usize {
+        self.age
+    }
+}
+
+/// The points scored by two warriors on the hill against each other, identified by their ids.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PairResult {
+    a: usize,
+    b: usize,
+    a_points: f64,
+    b_points: f64,
+    rounds: usize,
+}
+
+/// The outcome of a challenge to the hill.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeOutcome {
+    /// The challenger's rank on the hill, starting from 1, or `None` if it didn't stay on the hill.
+    pub rank: Option<usize>,
+    /// The warrior pushed off the hill by the challenge, if any.
+    pub pushed_off: Option<HillWarrior>,
+}
+
+impl Hill {
+    /// Creates an empty hill holding up to `size` warriors, fought with the settings of `preset`
+    /// for `rounds` rounds per pairing.
+    pub fn new(preset: Preset, size: usize, rounds: usize) -> Self {
+        Self {
+            preset,
+            size,
+            rounds,
+            next_id: 0,
+            warriors: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Loads a hill previously saved with [`save`](#method.save).
+    pub fn load(path: &Path) -> Result<Self, HillError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Saves the hill to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), HillError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// The preset the hill's battles are fought with.
+    pub fn preset(&self) -> Preset {
+        self.preset
+    }
+
+    /// The warriors on the hill, from first to last place.
+    pub fn warriors(&self) -> &[HillWarrior] {
+        &self.warriors
+    }
+
+    /// The results of the warriors on the hill against each other, in the same order as
+    /// [`warriors`](#method.warriors).
+    pub fn standings(&self) -> TournamentResults {
+        let mut standings = TournamentResults::new(
+            self.warriors
+                .iter()
+                .map(|w| format!("{} by {}", w.name, w.author))
+                .collect(),
+        );
+        for result in &self.results {
+            if let (Some(a), Some(b)) = (self.position(result.a), self.position(result.b)) {
+                standings.add_points(a, b, result.a_points, result.b_points, result.rounds);
+            }
+        }
+
+        standings
+    }
+
+    /// Challenges the hill with the warrior in `source`, fighting it against every warrior on the
+    /// hill with `runner`, then re-ranks the hill.
+    pub fn challenge(
+        &mut self,
+        source: &str,
+        runner: &Runner,
+    ) -> Result<ChallengeOutcome, HillError> {
+        let mut warriors = self
+            .warriors
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                Warrior::parse(&w.source, i).map_err(|e| HillError::Warrior(w.name.clone(), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let challenger = Warrior::parse(source, warriors.len())
+            .map_err(|e| HillError::Warrior(String::from("challenger"), e))?;
+        let id = self.next_id;
+        let challenger_entry = HillWarrior {
+            id,
+            name: challenger
+                .metadata
+                .name()
+                .unwrap_or("Unnamed warrior")
+                .to_owned(),
+            author: challenger
+                .metadata
+                .author()
+                .unwrap_or("Unnamed author")
+                .to_owned(),
+            source: source.to_owned(),
+            age: 0,
+        };
+        warriors.push(challenger);
+
+        let mut builder = CoreBuilder::new();
+        builder.preset(self.preset);
+
+        let challenger_position = warriors.len() - 1;
+        let pairings: Vec<(usize, usize)> = (0..challenger_position)
+            .map(|opponent| (challenger_position, opponent))
+            .collect();
+        let results = Tournament::new()
+            .rounds(self.rounds)
+            .runner(runner.clone())
+            .run_pairings(&builder, &warriors, &pairings, &Standard::new())?;
+
+        for &(_, opponent) in &pairings {
+            self.results.push(PairResult {
+                a: id,
+                b: self.warriors[opponent].id,
+                a_points: results.points(challenger_position, opponent),
+                b_points: results.points(opponent, challenger_position),
+                rounds: results.rounds(challenger_position, opponent),
+            });
+        }
+        for warrior in &mut self.warriors {
+            warrior.age += 1;
+        }
+        self.warriors.push(challenger_entry);
+        self.next_id += 1;
+
+        let standings = self.standings();
+        let mut ranked: Vec<HillWarrior> = standings
+            .ranking()
+            .into_iter()
+            .map(|i| self.warriors[i].clone())
+            .collect();
+
+        let pushed_off = if ranked.len() > self.size {
+            let pushed_off = ranked.pop();
+            if let Some(ref warrior) = pushed_off {
+                self.results
+                    .retain(|r| r.a != warrior.id && r.b != warrior.id);
+            }
+            pushed_off
+        } else {
+            None
+        };
+        self.warriors = ranked;
+
+        Ok(ChallengeOutcome {
+            rank: self.position(id).map(|p| p + 1),
+            pushed_off,
+        })
+    }
+
+    fn position(&self, id: usize) -> Option<usize> {
+        self.warriors.iter().position(|w| w.id == id)
+    }
+}
+
+impl Display for Hill {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} hill, {}/{} warriors, {} rounds per pairing",
+            self.preset,
+            self.warriors.len(),
+            self.size,
+            self.rounds
+        )?;
+        write!(
+            f,
+            "{:>4}  {:<30} {:<20} {:>4} {:>8}",
+            "Rank", "Name", "Author", "Age", "Score"
+        )?;
+
+        let standings = self.standings();
+        for (i, warrior) in self.warriors.iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4}  {:<30} {:<20} {:>4} {:>8.1}",
+                i + 1,
+                warrior.name,
+                warrior.author,
+                warrior.age,
+                standings.total(i)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn challenges_push_off_last_place() {
+        let mut hill = Hill::new(Preset::Tiny, 2, 4);
+        let runner = Runner::new();
+
+        let outcome = hill
+            .challenge(include_str!("../warriors/imp.red"), &runner)
+            .unwrap();
+        assert_eq!(outcome.rank, Some(1));
+        assert_eq!(outcome.pushed_off, None);
+
+        let outcome = hill
+            .challenge(include_str!("../warriors/dwarf.red"), &runner)
+            .unwrap();
+        assert!(outcome.rank.is_some());
+        assert_eq!(hill.warriors().len(), 2);
+        assert_eq!(hill.results.len(), 1);
+
+        let outcome = hill
+            .challenge(include_str!("../warriors/stone.red"), &runner)
+            .unwrap();
+        assert_eq!(hill.warriors().len(), 2);
+        assert!(outcome.pushed_off.is_some());
+        assert_eq!(hill.results.len(), 1);
+        assert_eq!(hill.standings().rounds(0, 1), 4);
+
+        let oldest = hill.warriors().iter().map(|w| w.age()).max().unwrap();
+        assert!(oldest >= 1);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut hill = Hill::new(Preset::Nano, 5, 2);
+        hill.challenge(include_str!("../warriors/imp.red"), &Runner::new())
+            .unwrap();
+
+        let json = serde_json::to_string(&hill).unwrap();
+        let loaded: Hill = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, hill);
+    }
+}
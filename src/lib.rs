@@ -23,8 +23,22 @@
 //!         end
 //! ```
 //!
+pub mod bench;
+pub mod classify;
+pub mod compare;
 pub mod core;
+pub mod debugger;
 pub mod error;
+pub mod evolver;
+pub mod hill;
 pub mod logger;
+pub mod optimiser;
 pub(crate) mod parser;
+pub mod profiler;
+pub mod render;
+pub mod replay;
+pub mod runner;
+pub mod score;
+pub mod sensitivity;
+pub mod tournament;
 pub mod warrior;
@@ -0,0 +1,150 @@
+mod stats;
+mod trace;
+pub use stats::{Kills, StatsLogger, StatsSample, WarriorStats};
+pub use trace::TraceLogger;
+
+use crate::{
+    core::{CoreInstruction, MatchOutcome, Ownership},
+    warrior::Warrior,
+};
+use std::fmt::Debug;
+
+use super::core::Core;
+
+pub enum GameEvent<'a> {
+    WarriorKilled(&'a Warrior),
+    GameOver(MatchOutcome<'a>),
+    Continue,
+    /// A single instruction was executed. Only sent if a logger is attached, so that the core
+    /// doesn't need to keep track of what each instruction did otherwise.
+    Executed(Execution<'a>),
+    /// A process died. Sent straight after the `Executed` event for the instruction that killed
+    /// it. The warrior itself is only killed, with `WarriorKilled`, when it next gets a turn
+    /// with no processes left.
+    ProcessKilled(ProcessDeath<'a>),
+}
+
+/// The death of a single process, and what killed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessDeath<'a> {
+    /// The warrior the process belonged to.
+    pub warrior: &'a Warrior,
+    /// The address of the instruction the process died executing.
+    pub pc: usize,
+    /// The instruction the process died executing.
+    pub instruction: CoreInstruction,
+    /// The warrior that last wrote to the instruction, if ownership is being tracked and any
+    /// warrior has.
+    pub writer: Option<Ownership>,
+    pub cause: DeathCause,
+    /// The cycle the process died on.
+    pub cycle: usize,
+}
+
+/// What killed a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DeathCause {
+    /// Executing a `DAT` written, or loaded, by the warrior with this index, which may be the
+    /// process's own warrior.
+    Warrior(usize),
+    /// Executing a `DAT` that no warrior has written to, left over from the core's initial
+    /// instruction.
+    InitialCore,
+    /// Dividing by zero with `DIV` or `MOD`.
+    DivideByZero,
+    /// Executing a `DAT` whose writer isn't known, as ownership isn't being tracked.
+    Unknown,
+}
+
+/// Everything that happened while a single instruction was executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Execution<'a> {
+    /// The warrior whose process executed the instruction.
+    pub warrior: &'a Warrior,
+    /// The address of the executed instruction.
+    pub pc: usize,
+    /// The instruction, as it was when it was executed.
+    pub instruction: CoreInstruction,
+    /// The address the A operand resolved to.
+    pub a_ptr: usize,
+    /// The address the B operand resolved to.
+    pub b_ptr: usize,
+    /// Every access to a cell of the core, in the order they happened.
+    pub accesses: Vec<CellAccess>,
+    /// The addresses of the tasks added to the warrior's task queue: the process's next
+    /// instruction, and any process it split off.
+    pub queued: Vec<usize>,
+}
+
+impl Execution<'_> {
+    /// Whether the process that executed the instruction died, rather than queuing its next
+    /// instruction.
+    pub fn process_died(&self) -> bool {
+        self.queued.is_empty()
+    }
+}
+
+/// An access to a single cell of the core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellAccess {
+    pub address: usize,
+    pub kind: AccessKind,
+}
+
+/// The ways a cell of the core can be accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    Write,
+    /// One of the cell's fields was incremented by an addressing mode.
+    Increment,
+    /// One of the cell's fields was decremented, by an addressing mode or `DJN`.
+    Decrement,
+}
+
+/// Something which is told about the events in a core, attached with
+/// [`Core::log_with`](../core/struct.Core.html#method.log_with).
+pub trait Logger: Debug + Send {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent);
+
+    /// Forget anything collected so far, ready for another round. Does nothing by default.
+    fn reset(&mut self) {}
+}
+
+#[derive(Debug)]
+pub struct DebugLogger {}
+
+impl DebugLogger {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Default for DebugLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// TODO: Make this actually good
+impl Logger for DebugLogger {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        match event {
+            GameEvent::WarriorKilled(warrior) => {
+                println!(
+                    "Killing a warrior: {} after {} cycles",
+                    warrior,
+                    current_game_state.cycle_count()
+                );
+            }
+            GameEvent::GameOver(outcome) => {
+                println!(
+                    "Game over! {} after {} cycles",
+                    outcome,
+                    current_game_state.cycle_count()
+                );
+            }
+            _ => {}
+        }
+    }
+}
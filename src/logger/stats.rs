@@ -0,0 +1,372 @@
+use super::{DeathCause, GameEvent, Logger};
+use crate::{core::Core, parser::instruction::Opcode};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+/// The statistics collected for a single warrior.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WarriorStats {
+    /// The warrior's name and author.
+    pub name: String,
+    /// The number of instructions the warrior executed.
+    pub executed: usize,
+    /// The number of times the warrior executed each opcode.
+    pub opcodes: BTreeMap<String, usize>,
+    /// The number of times the warrior executed each modifier.
+    pub modifiers: BTreeMap<String, usize>,
+    /// The number of `SPL` instructions the warrior executed.
+    pub splits: usize,
+    /// The most processes the warrior had at once.
+    pub max_processes: usize,
+    /// The cycle the warrior died on, or `None` if it survived.
+    pub died_at: Option<usize>,
+    /// The number of the warrior's processes killed by each cause.
+    pub process_deaths: BTreeMap<DeathCause, usize>,
+    /// What killed the warrior's last process, or `None` if it survived.
+    pub killed_by: Option<DeathCause>,
+}
+
+/// The number of processes and cells each warrior had at a point in a round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsSample {
+    pub cycle: usize,
+    /// The number of processes of each warrior, by warrior index.
+    pub processes: Vec<usize>,
+    /// The number of cells of the core each warrior owns, by warrior index, as tracked by the
+    /// core (see [`Core::owners`](../core/struct.Core.html#method.owners)). A cell is owned by
+    /// the warrior that loaded or last wrote to it. Every count is 0 if the core doesn't track
+    /// ownership.
+    pub cells: Vec<usize>,
+}
+
+/// A `Logger` that collects statistics about each warrior over a round, sampling the number of
+/// processes and cells each warrior has every `interval` cycles.
+#[derive(Debug, Clone)]
+pub struct StatsLogger {
+    interval: usize,
+    warriors: Vec<WarriorStats>,
+    samples: Vec<StatsSample>,
+    last_deaths: Vec<Option<DeathCause>>,
+    cycles: usize,
+}
+
+impl StatsLogger {
+    /// Creates a new `StatsLogger`, which samples every `interval` cycles.
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            warriors: Vec::new(),
+            samples: Vec::new(),
+            last_deaths: Vec::new(),
+            cycles: 0,
+        }
+    }
+
+    /// The statistics collected for each warrior, by warrior index.
+    pub fn warriors(&self) -> &[WarriorStats] {
+        &self.warriors
+    }
+
+    /// The samples taken so far, in order.
+    pub fn samples(&self) -> &[StatsSample] {
+        &self.samples
+    }
+
+    /// The number of cycles the round has lasted so far.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// A summary of what killed each warrior and its processes, which can be printed on its
+    /// own. The full statistics printed by `Display` include it too.
+    pub fn kills(&self) -> Kills<'_> {
+        Kills(self)
+    }
+
+    /// Writes what killed the warrior with index `idx`, and what killed its processes, if
+    /// anything did.
+    fn write_kills(&self, f: &mut Formatter<'_>, idx: usize) -> std::fmt::Result {
+        let warrior = &self.warriors[idx];
+        if let Some(cause) = warrior.killed_by {
+            writeln!(f, "  Killed by: {}", self.describe(cause, idx))?;
+        }
+        if !warrior.process_deaths.is_empty() {
+            let deaths: Vec<String> = warrior
+                .process_deaths
+                .iter()
+                .map(|(cause, count)| format!("{} {}", self.describe(*cause, idx), count))
+                .collect();
+            writeln!(f, "  Processes killed by: {}", deaths.join(", "))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the samples to `writer` as CSV, with a row for each warrior in each sample.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "cycle,warrior,name,processes,cells")?;
+        for sample in &self.samples {
+            for (idx, warrior) in self.warriors.iter().enumerate() {
+                writeln!(
+                    writer,
+                    "{},{},\"{}\",{},{}",
+                    sample.cycle,
+                    idx,
+                    warrior.name.replace('"', "\"\""),
+                    sample.processes[idx],
+                    sample.cells[idx]
+                )?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    fn start(&mut self, core: &Core) {
+        let warriors = core
+            .task_queues()
+            .iter()
+            .map(|(w, _)| w.idx + 1)
+            .max()
+            .unwrap_or(0);
+        self.warriors = vec![WarriorStats::default(); warriors];
+        for (warrior, _) in core.task_queues() {
+            self.warriors[warrior.idx].name = warrior.metadata.display_name();
+            self.warriors[warrior.idx].max_processes = 1;
+        }
+        self.last_deaths = vec![None; warriors];
+    }
+
+    fn describe(&self, cause: DeathCause, victim: usize) -> String {
+        match cause {
+            DeathCause::Warrior(killer) if killer == victim => String::from("itself"),
+            DeathCause::Warrior(killer) => self.warriors[killer].name.clone(),
+            DeathCause::InitialCore => String::from("initial core"),
+            DeathCause::DivideByZero => String::from("division by zero"),
+            DeathCause::Unknown => String::from("unknown"),
+        }
+    }
+
+    fn sample(&mut self, core: &Core) {
+        let mut processes = vec![0; self.warriors.len()];
+        for (warrior, queue) in core.task_queues() {
+            processes[warrior.idx] = queue.len();
+        }
+        let mut cells = vec![0; self.warriors.len()];
+        for owner in core.owners().unwrap_or_default().iter().flatten() {
+            if let Some(count) = cells.get_mut(owner.warrior) {
+                *count += 1;
+            }
+        }
+
+        self.samples.push(StatsSample {
+            cycle: core.cycle_count(),
+            processes,
+            cells,
+        });
+    }
+}
+
+impl Logger for StatsLogger {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        if self.warriors.is_empty() {
+            self.start(current_game_state);
+        }
+        let cycle = current_game_state.cycle_count();
+        self.cycles = cycle;
+
+        match event {
+            GameEvent::Executed(execution) => {
+                let idx = execution.warrior.idx;
+                let stats = &mut self.warriors[idx];
+
+                stats.executed += 1;
+                *stats
+                    .opcodes
+                    .entry(execution.instruction.opcode().to_string())
+                    .or_default() += 1;
+                *stats
+                    .modifiers
+                    .entry(execution.instruction.modifier().to_string())
+                    .or_default() += 1;
+                if *execution.instruction.opcode() == Opcode::Spl {
+                    stats.splits += 1;
+                }
+                if let Some((_, queue)) = current_game_state
+                    .task_queues()
+                    .iter()
+                    .find(|(w, _)| w.idx == idx)
+                {
+                    stats.max_processes = stats.max_processes.max(queue.len());
+                }
+
+                if cycle.is_multiple_of(self.interval) {
+                    self.sample(current_game_state);
+                }
+            }
+            GameEvent::ProcessKilled(death) => {
+                let idx = death.warrior.idx;
+                *self.warriors[idx]
+                    .process_deaths
+                    .entry(death.cause)
+                    .or_default() += 1;
+                self.last_deaths[idx] = Some(death.cause);
+            }
+            GameEvent::WarriorKilled(warrior) => {
+                self.warriors[warrior.idx].died_at = Some(cycle);
+                self.warriors[warrior.idx].killed_by = self.last_deaths[warrior.idx];
+            }
+            GameEvent::GameOver(_) => self.sample(current_game_state),
+            GameEvent::Continue => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.interval);
+    }
+}
+
+impl Display for StatsLogger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let last = self.samples.last();
+        writeln!(
+            f,
+            "{:<30} {:>9} {:>6} {:>10} {:>6} {:>10}",
+            "Warrior", "Executed", "SPLs", "Processes", "Cells", "Died"
+        )?;
+        for (idx, warrior) in self.warriors.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<30} {:>9} {:>6} {:>10} {:>6} {:>10}",
+                warrior.name,
+                warrior.executed,
+                warrior.splits,
+                warrior.max_processes,
+                last.map_or(0, |s| s.cells[idx]),
+                warrior
+                    .died_at
+                    .map_or_else(|| String::from("-"), |c| c.to_string())
+            )?;
+        }
+
+        for (idx, warrior) in self.warriors.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "{}", warrior.name)?;
+            self.write_kills(f, idx)?;
+            let histogram = |counts: &BTreeMap<String, usize>| {
+                counts
+                    .iter()
+                    .map(|(key, count)| format!("{} {}", key, count))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            writeln!(f, "  Opcodes:   {}", histogram(&warrior.opcodes))?;
+            write!(f, "  Modifiers: {}", histogram(&warrior.modifiers))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What killed each warrior in a round, and its processes, as collected by a
+/// [`StatsLogger`](struct.StatsLogger.html).
+#[derive(Debug, Clone, Copy)]
+pub struct Kills<'s>(&'s StatsLogger);
+
+impl Display for Kills<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, warrior) in self.0.warriors.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", warrior.name)?;
+            if warrior.killed_by.is_none() {
+                writeln!(f, "  Survived")?;
+            }
+            self.0.write_kills(f, idx)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{CoreBuilder, MatchOutcome, Separation},
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn collects_stats() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(1000)
+            .separation(Separation::Fixed(1000))
+            .track_ownership(true)
+            .load_warriors(&[imp, dwarf])
+            .unwrap();
+        let mut stats = StatsLogger::new(100);
+
+        let mut core = builder.build().unwrap();
+        let outcome = core.log_with(&mut stats).run();
+        drop(core);
+
+        let [imp, dwarf] = stats.warriors() else {
+            panic!("expected two warriors");
+        };
+        assert_eq!((imp.executed, dwarf.executed), (500, 500));
+        assert_eq!(imp.opcodes["MOV"], 500);
+        assert_eq!(dwarf.opcodes["ADD"] + dwarf.opcodes["MOV"], 334);
+        assert_eq!(imp.splits + dwarf.splits, 0);
+        assert_eq!(imp.died_at, None);
+
+        // The imp owns every cell it has moved through, the dwarf its code and its bombs.
+        let last = stats.samples().last().unwrap();
+        assert_eq!(stats.samples().len(), 11);
+        assert_eq!(last.processes, vec![1, 1]);
+        assert_eq!(last.cells, vec![501, 4 + 167]);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 23);
+        // The outcome borrows the warriors, not the logger.
+        assert!(matches!(outcome, MatchOutcome::Draw(ref w) if w.len() == 2));
+
+        stats.reset();
+        assert!(stats.warriors().is_empty() && stats.samples().is_empty());
+    }
+
+    #[test]
+    fn summarises_kills() {
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+        let dat = Warrior::parse(
+            ";name Dat\n;author Nobody\n        ORG 0\n        DAT.F #0, #0\n        END\n",
+            1,
+        )
+        .unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .separation(Separation::Fixed(1000))
+            .track_ownership(true)
+            .load_warriors(&[dwarf, dat])
+            .unwrap();
+        let mut stats = StatsLogger::new(100);
+
+        let mut core = builder.build().unwrap();
+        core.log_with(&mut stats).run();
+        drop(core);
+
+        assert_eq!(
+            stats.kills().to_string(),
+            "Dwarf by A. K. Dewdney\n  Survived\n\n\
+             Dat by Nobody\n  Killed by: itself\n  Processes killed by: itself 1\n"
+        );
+    }
+}
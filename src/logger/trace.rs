@@ -0,0 +1,216 @@
+use super::{AccessKind, DeathCause, GameEvent, Logger};
+use crate::core::{Core, MatchOutcome};
+use serde::Serialize;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// A single line of a trace.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Record {
+    Executed {
+        cycle: usize,
+        warrior: usize,
+        pc: usize,
+        instruction: String,
+        a_ptr: usize,
+        b_ptr: usize,
+        /// The addresses of every cell written to, incremented or decremented.
+        writes: Vec<usize>,
+    },
+    ProcessKilled {
+        cycle: usize,
+        warrior: usize,
+        pc: usize,
+        instruction: String,
+        /// One of `dat`, `initial_core`, `divide_by_zero` or `unknown`.
+        cause: &'static str,
+        /// The warrior that wrote the `DAT` the process executed, for the `dat` cause.
+        killer: Option<usize>,
+    },
+    Killed {
+        cycle: usize,
+        warrior: usize,
+    },
+    GameOver {
+        cycle: usize,
+        /// The warriors still alive: just the winner, unless the match was a draw.
+        survivors: Vec<usize>,
+        draw: bool,
+    },
+}
+
+/// A `Logger` that writes a trace of a match as [JSON Lines](https://jsonlines.org): one JSON
+/// object per executed instruction, process killed, warrior killed and game over, with an
+/// `event` field saying which.
+///
+/// Logging can't fail, so the first error writing the trace is kept, and no more of the trace is
+/// written. It's returned by [`finish`](#method.finish).
+pub struct TraceLogger<W: Write + Send> {
+    writer: W,
+    cycles: Option<Range<usize>>,
+    warrior: Option<usize>,
+    error: Option<io::Error>,
+}
+
+impl<W: Write + Send> TraceLogger<W> {
+    /// Creates a new `TraceLogger` writing to `writer`. It's worth buffering `writer`, as every
+    /// record is written separately.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            cycles: None,
+            warrior: None,
+            error: None,
+        }
+    }
+
+    /// Only trace instructions executed, and processes and warriors killed, during these cycles.
+    pub fn cycles(&mut self, cycles: Range<usize>) -> &mut Self {
+        self.cycles = Some(cycles);
+        self
+    }
+
+    /// Only trace the instructions executed by, and the deaths of, the warrior with this index.
+    pub fn warrior(&mut self, idx: usize) -> &mut Self {
+        self.warrior = Some(idx);
+        self
+    }
+
+    /// Flushes the trace, returning the writer, or the first error writing to it.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn traced(&self, cycle: usize, warrior: usize) -> bool {
+        self.cycles.as_ref().is_none_or(|c| c.contains(&cycle))
+            && self.warrior.is_none_or(|w| w == warrior)
+    }
+
+    fn write(&mut self, record: &Record) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = serde_json::to_writer(&mut self.writer, record)
+            .map_err(io::Error::from)
+            .and_then(|_| self.writer.write_all(b"\n"));
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<W: Write + Send> Debug for TraceLogger<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceLogger")
+            .field("cycles", &self.cycles)
+            .field("warrior", &self.warrior)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<W: Write + Send> Logger for TraceLogger<W> {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        let cycle = current_game_state.cycle_count();
+        let record = match event {
+            GameEvent::Executed(execution) if self.traced(cycle, execution.warrior.idx) => {
+                Record::Executed {
+                    cycle,
+                    warrior: execution.warrior.idx,
+                    pc: execution.pc,
+                    instruction: execution.instruction.to_string(),
+                    a_ptr: execution.a_ptr,
+                    b_ptr: execution.b_ptr,
+                    writes: execution
+                        .accesses
+                        .iter()
+                        .filter(|access| access.kind != AccessKind::Read)
+                        .map(|access| access.address)
+                        .collect(),
+                }
+            }
+            GameEvent::ProcessKilled(death) if self.traced(cycle, death.warrior.idx) => {
+                let (cause, killer) = match death.cause {
+                    DeathCause::Warrior(killer) => ("dat", Some(killer)),
+                    DeathCause::InitialCore => ("initial_core", None),
+                    DeathCause::DivideByZero => ("divide_by_zero", None),
+                    DeathCause::Unknown => ("unknown", None),
+                };
+                Record::ProcessKilled {
+                    cycle,
+                    warrior: death.warrior.idx,
+                    pc: death.pc,
+                    instruction: death.instruction.to_string(),
+                    cause,
+                    killer,
+                }
+            }
+            GameEvent::WarriorKilled(warrior) if self.traced(cycle, warrior.idx) => {
+                Record::Killed {
+                    cycle,
+                    warrior: warrior.idx,
+                }
+            }
+            GameEvent::GameOver(outcome) => Record::GameOver {
+                cycle,
+                survivors: match outcome {
+                    MatchOutcome::Win(warrior) => vec![warrior.idx],
+                    MatchOutcome::Draw(warriors) => warriors.iter().map(|w| w.idx).collect(),
+                },
+                draw: matches!(outcome, MatchOutcome::Draw(_)),
+            },
+            _ => return,
+        };
+
+        self.write(&record);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::CoreBuilder, warrior::Warrior};
+    use serde_json::Value;
+
+    #[test]
+    fn traces_a_window_of_one_warrior() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(100)
+            .load_warriors(&[imp, dwarf])
+            .unwrap();
+        let mut logger = TraceLogger::new(Vec::new());
+        logger.cycles(10..20).warrior(1);
+
+        let mut core = builder.build().unwrap();
+        core.log_with(&mut logger).run();
+        drop(core);
+
+        let trace = String::from_utf8(logger.finish().unwrap()).unwrap();
+        let records: Vec<Value> = trace
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 6);
+        for record in &records[..5] {
+            assert_eq!(record["event"], "executed");
+            assert_eq!(record["warrior"], 1);
+            let cycle = record["cycle"].as_u64().unwrap();
+            assert!((10..20).contains(&cycle));
+        }
+        assert_eq!(records[5]["event"], "game_over");
+        assert_eq!(records[5]["draw"], true);
+    }
+}
@@ -1,17 +1,39 @@
-use anyhow::Error;
 use anyhow::Result;
-use indicatif::{ParallelProgressIterator, ProgressIterator};
-use mars::{core::Core, core::MatchOutcome, logger::DebugLogger, warrior::Warrior};
+use anyhow::{Context, Error};
+use mars::{
+    bench::Benchmark,
+    classify::Classifier,
+    compare::Comparison,
+    core::{Core, CoreBuilder, MatchOutcome, Preset},
+    debugger::{DebugCommand, Debugger},
+    evolver::{Evolver, Fitness},
+    hill::Hill,
+    logger::{DebugLogger, StatsLogger, TraceLogger},
+    optimiser::{Optimiser, Strategy},
+    profiler::Profiler,
+    render::{Activity, Animation, Heatmap, Layout},
+    replay::Replay,
+    runner::{Adaptive, Runner},
+    score::{Melee, Results, RoundResult, Scoring, Standard},
+    sensitivity::Sweep,
+    tournament::Tournament,
+    warrior::Warrior,
+};
 use rayon::prelude::*;
 use std::collections::HashMap;
-use std::path::Path;
-use std::{fs::File, io::Read};
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 mod visual;
 
 #[derive(StructOpt)]
 struct Opt {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+
     #[structopt(short, long)]
     /// Paths to the warrior files to be used
     warriors: Vec<String>,
@@ -20,6 +42,10 @@ struct Opt {
     #[structopt(short, long)]
     core_size: Option<usize>,
 
+    /// The standard settings to start from: standard, tiny, nano, lp or experimental.
+    #[structopt(long)]
+    preset: Option<Preset>,
+
     /// The number of times the match should be repeated.
     #[structopt(short, long, default_value = "1")]
     matches: usize,
@@ -28,132 +54,1237 @@ struct Opt {
     #[structopt(long)]
     single_threaded: bool,
 
+    /// Keep running matches in batches until the 95% confidence interval on the difference in
+    /// score between every pair of warriors is narrower than this many percentage points.
+    #[structopt(long)]
+    precision: Option<f64>,
+
+    /// The number of matches to run between precision checks.
+    #[structopt(long, default_value = "100")]
+    batch_size: usize,
+
+    /// The minimum number of matches to run when running until a given precision.
+    #[structopt(long, default_value = "30")]
+    min_matches: usize,
+
+    /// The maximum number of matches to run when running until a given precision.
+    #[structopt(long, default_value = "100000")]
+    max_matches: usize,
+
     /// Run once with visualiser
     #[structopt(long = "visualiser", short = "v")]
     with_visualiser: bool,
+
+    /// Override an EQU constant, as name=value, in the warriors given with -w, or the warriors
+    /// being benchmarked, compared or optimised
+    #[structopt(
+        short = "D",
+        long = "define",
+        number_of_values = 1,
+        parse(try_from_str = parse_define)
+    )]
+    defines: Vec<(String, String)>,
+
+    /// Add constants given with -D even if the warriors don't define them
+    #[structopt(long)]
+    force_defines: bool,
+
+    /// Write a JSON Lines trace of a single match to this file, or to stdout if it's -
+    #[structopt(long, parse(from_os_str))]
+    trace: Option<PathBuf>,
+
+    /// Only trace the cycles in this range, given as start..end
+    #[structopt(long, parse(try_from_str = parse_cycles))]
+    trace_cycles: Option<Range<usize>>,
+
+    /// Only trace the warrior at this position in the list given with -w, counting from 0
+    #[structopt(long)]
+    trace_warrior: Option<usize>,
+
+    /// Save a single match to this file, so it can be run again with the replay command
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// The seed to place the warriors from in a single match, rather than a random one
+    #[structopt(long)]
+    seed: Option<u64>,
+
+    /// Print statistics about each warrior after a single match. Without this, a single match
+    /// still prints what killed each warrior and its processes
+    #[structopt(long)]
+    stats: bool,
+
+    /// Write the number of processes and cells each warrior has over a single match to this CSV
+    /// file
+    #[structopt(long, parse(from_os_str))]
+    stats_csv: Option<PathBuf>,
+
+    /// The number of cycles between each row of the statistics CSV
+    #[structopt(long, default_value = "100")]
+    stats_interval: usize,
+}
+
+fn parse_cycles(s: &str) -> Result<Range<usize>, String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected start..end, got {}", s))?;
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|e| format!("{}: {}", n, e))
+    };
+
+    Ok(parse(start)?..parse(end)?)
+}
+
+fn parse_define(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(format!("Expected name=value, found {}", s)),
+    }
+}
+
+/// EQU overrides given on the command line.
+#[derive(Default)]
+struct Defines {
+    values: HashMap<String, String>,
+    force: bool,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Run a round-robin tournament between every warrior in a directory
+    Tournament {
+        /// The directory containing the warriors' .red files
+        #[structopt(parse(from_os_str))]
+        directory: PathBuf,
+
+        /// The number of rounds each pairing is fought for.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+
+        /// Have each warrior fight a copy of itself too
+        #[structopt(long)]
+        self_play: bool,
+
+        /// Fight each pairing with each warrior loaded first in turn
+        #[structopt(long)]
+        both_orders: bool,
+    },
+    /// Score a warrior against every warrior in a benchmark directory
+    Bench {
+        /// The path to the warrior's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+
+        /// The directory containing the benchmark warriors' .red files
+        #[structopt(parse(from_os_str))]
+        directory: PathBuf,
+
+        /// The number of rounds fought against each benchmark warrior.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+
+        /// Print the report as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Compare two versions of a warrior against every warrior in a directory, using the same
+    /// starting positions for both
+    Compare {
+        /// The path to the original version's .red file
+        #[structopt(parse(from_os_str))]
+        baseline: PathBuf,
+
+        /// The path to the new version's .red file
+        #[structopt(parse(from_os_str))]
+        candidate: PathBuf,
+
+        /// The directory containing the opponents' .red files
+        #[structopt(parse(from_os_str))]
+        directory: PathBuf,
+
+        /// The number of rounds each version fights against each opponent.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+
+        /// The seed to derive the rounds' seeds from, to repeat a comparison exactly.
+        #[structopt(long)]
+        seed: Option<u64>,
+
+        /// Print the report as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Fight a warrior against an opponent loaded at every offset in a range, showing which
+    /// offsets it wins, loses and ties from
+    Sweep {
+        /// The path to the warrior's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+
+        /// The path to the opponent's .red file
+        #[structopt(parse(from_os_str))]
+        opponent: PathBuf,
+
+        /// The first offset, from the warrior's first instruction to the opponent's, to try.
+        /// Defaults to the closest the minimum separation allows.
+        #[structopt(long)]
+        from: Option<usize>,
+
+        /// The last offset to try. Defaults to the furthest the minimum separation allows.
+        #[structopt(long)]
+        to: Option<usize>,
+
+        /// The distance between the offsets tried.
+        #[structopt(long, default_value = "1")]
+        step: usize,
+
+        /// The number of rounds fought at each offset.
+        #[structopt(short, long, default_value = "1")]
+        rounds: usize,
+
+        /// Write the results at each offset to this CSV file
+        #[structopt(long, parse(from_os_str))]
+        csv: Option<PathBuf>,
+
+        /// Print the report as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Show how many times each line of a warrior is executed, over rounds against some
+    /// opponents, or on its own
+    Profile {
+        /// The path to the warrior's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+
+        /// The paths to the opponents' .red files
+        #[structopt(parse(from_os_str))]
+        opponents: Vec<PathBuf>,
+
+        /// The number of rounds to add up.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+    },
+    /// Evolve warriors with a genetic algorithm, saving the population as .red files
+    Evolve {
+        /// The directory the population is saved in
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+
+        /// Measure fitness against the warriors in this directory, rather than against the rest
+        /// of the population
+        #[structopt(long, parse(from_os_str))]
+        benchmark: Option<PathBuf>,
+
+        /// Start from the warriors in this directory, rather than from random warriors
+        #[structopt(long, parse(from_os_str))]
+        seeds: Option<PathBuf>,
+
+        /// The number of generations to breed.
+        #[structopt(short, long, default_value = "100")]
+        generations: usize,
+
+        /// The number of warriors in each generation.
+        #[structopt(short, long, default_value = "50")]
+        population: usize,
+
+        /// The number of rounds fought in each pairing when measuring fitness.
+        #[structopt(short, long, default_value = "20")]
+        rounds: usize,
+
+        /// Save the population every this many generations, as well as after the last.
+        #[structopt(long, default_value = "10")]
+        checkpoint_every: usize,
+    },
+    /// Find the values of a warrior's EQU constants marked with ;tune that score best against
+    /// every warrior in a directory
+    Optimise {
+        /// The path to the warrior's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+
+        /// The directory containing the opponents' .red files
+        #[structopt(parse(from_os_str))]
+        directory: PathBuf,
+
+        /// How to choose the values to try: grid, random or hill-climb.
+        #[structopt(short, long, default_value = "hill-climb")]
+        strategy: String,
+
+        /// The number of random combinations of values to try with the random strategy.
+        #[structopt(long, default_value = "100")]
+        samples: usize,
+
+        /// The number of times to restart from random values with the hill-climb strategy.
+        #[structopt(long, default_value = "3")]
+        restarts: usize,
+
+        /// The largest number of combinations of values the grid strategy will try.
+        #[structopt(long, default_value = "10000")]
+        max_candidates: usize,
+
+        /// The number of rounds each candidate fights against each opponent.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+
+        /// The number of best candidates to show.
+        #[structopt(long, default_value = "10")]
+        top: usize,
+    },
+    /// Run a local King-of-the-Hill, saved in a file
+    Hill {
+        /// The file the hill is saved in
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+
+        #[structopt(subcommand)]
+        action: HillAction,
+    },
+    /// Run a round saved with --record again, checking it ends the same way
+    Replay {
+        /// The replay file
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+
+        /// Watch the round in the visualiser, rather than checking its outcome
+        #[structopt(long = "visualiser", short = "v")]
+        with_visualiser: bool,
+    },
+    /// Draw how often each warrior given with -w reads, writes and executes each cell of the
+    /// core, over one or more rounds
+    Heatmap {
+        /// The image to write, as a PPM if it ends in .ppm and as a PNG otherwise
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+
+        /// The number of rounds to add up.
+        #[structopt(short, long, default_value = "1")]
+        rounds: usize,
+
+        /// The activity to draw: read, write, execute or all.
+        #[structopt(short, long, default_value = "all")]
+        activity: Activity,
+
+        /// The width and height of each cell, in pixels.
+        #[structopt(short, long, default_value = "4")]
+        scale: usize,
+    },
+    /// Guess whether a warrior is a stone, paper, scanner, imp or clear from how it behaves on
+    /// its own
+    Classify {
+        /// The path to the warrior's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+
+        /// The number of cycles to run the warrior for.
+        #[structopt(long, default_value = "20000")]
+        cycles: usize,
+
+        /// Print the classification as JSON
+        #[structopt(long)]
+        json: bool,
+    },
+    /// Draw a round between the warriors given with -w as an animated GIF, or as numbered PNGs
+    Animate {
+        /// The GIF to write if it ends in .gif, otherwise the directory to write the PNGs in
+        #[structopt(parse(from_os_str))]
+        output: PathBuf,
+
+        /// The number of cycles between frames.
+        #[structopt(short, long, default_value = "100")]
+        interval: usize,
+
+        /// How long to show each frame of a GIF for, in hundredths of a second.
+        #[structopt(short, long, default_value = "5")]
+        delay: u16,
+
+        /// The width and height of each cell, in pixels.
+        #[structopt(short, long, default_value = "4")]
+        scale: usize,
+
+        /// The largest number of frames to draw. Frames are kept in memory until the round
+        /// ends, so long rounds with a short interval should raise it with care.
+        #[structopt(long, default_value = "1000")]
+        max_frames: usize,
+    },
+    /// Step through a round between the warriors given with -w, with breakpoints, watchpoints
+    /// and cell editing, at a prompt
+    Debug,
+}
+
+#[derive(StructOpt)]
+enum HillAction {
+    /// Create a new, empty hill
+    Init {
+        /// The settings the hill's battles are fought with.
+        #[structopt(long, default_value = "standard")]
+        preset: Preset,
+
+        /// The number of warriors the hill holds.
+        #[structopt(short, long, default_value = "20")]
+        size: usize,
+
+        /// The number of rounds each pairing is fought for.
+        #[structopt(short, long, default_value = "100")]
+        rounds: usize,
+    },
+    /// Challenge the hill with a new warrior
+    Challenge {
+        /// The path to the challenger's .red file
+        #[structopt(parse(from_os_str))]
+        warrior: PathBuf,
+    },
+    /// Show the hill's standings
+    Standings,
 }
 
-fn load_warriors(warriors: Vec<String>) -> Result<Vec<Warrior>> {
+fn load_warriors<P: AsRef<Path> + Sync>(warriors: &[P], defines: &Defines) -> Result<Vec<Warrior>> {
     warriors
         .par_iter()
-        .map(Path::new)
         .map(|p| {
-            let mut file = File::open(p)?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents)?;
-            Ok(contents)
+            let p = p.as_ref();
+            std::fs::read_to_string(p).with_context(|| format!("Couldn't read {}", p.display()))
         })
         .enumerate()
         .map(|(i, s): (usize, Result<String>)| {
-            let warrior = Warrior::parse(&s?, i)?;
+            let warrior = Warrior::parse_with_overrides(&s?, i, &defines.values, defines.force)
+                .with_context(|| format!("Couldn't load {}", warriors[i].as_ref().display()))?;
             Ok(warrior)
         })
         .collect()
 }
 
-fn declare_results(match_results: Vec<MatchOutcome>, participants: &[Warrior]) -> String {
-    let mut results: HashMap<&str, usize> = participants
-        .iter()
-        .map(|warrior| (warrior.metadata.name().unwrap_or_default(), 0))
-        .collect();
-
-    results.insert("Draw", 0);
-    for outcome in match_results {
-        match outcome {
-            MatchOutcome::Win(winner) => {
-                let res = results
-                    .entry(winner.metadata.name().unwrap_or_default())
-                    .or_insert(0);
-                *res += 1;
+/// The loggers and recording for a single match.
+struct SingleMatch {
+    seed: u64,
+    trace: Option<PathBuf>,
+    trace_cycles: Option<Range<usize>>,
+    trace_warrior: Option<usize>,
+    record: Option<PathBuf>,
+    stats: bool,
+    stats_csv: Option<PathBuf>,
+    stats_interval: usize,
+}
+
+fn run_single(builder: &CoreBuilder, options: SingleMatch) -> Result<()> {
+    let trace_to_stdout = options.trace.as_deref() == Some(Path::new("-"));
+
+    let mut trace = match options.trace {
+        Some(ref path) => {
+            let writer: Box<dyn Write + Send> = if trace_to_stdout {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                let file = File::create(path)
+                    .with_context(|| format!("Couldn't create {}", path.display()))?;
+                Box::new(BufWriter::new(file))
+            };
+            let mut logger = TraceLogger::new(writer);
+            if let Some(ref cycles) = options.trace_cycles {
+                logger.cycles(cycles.clone());
             }
-            MatchOutcome::Draw(_) => {
-                let res = results.entry("Draw").or_insert(0);
-                *res += 1;
+            if let Some(warrior) = options.trace_warrior {
+                logger.warrior(warrior);
             }
+            Some(logger)
         }
+        None => None,
+    };
+    // Statistics are always collected, as what killed each warrior is printed after every
+    // single match.
+    let mut stats = StatsLogger::new(options.stats_interval);
+    let mut debug = DebugLogger::new();
+
+    let mut core = builder.build_with_seed(options.seed)?;
+    // Nothing else is printed when the trace is going to stdout, so it can be piped.
+    if !trace_to_stdout {
+        core.log_with(&mut debug);
+    }
+    if let Some(ref mut trace) = trace {
+        core.log_with(trace);
+    }
+    core.log_with(&mut stats);
+
+    let outcome = core.run();
+    if let Some(ref path) = options.record {
+        save_replay(path, &core, &outcome)?;
+    }
+    drop(core);
+
+    if let (Some(trace), Some(path)) = (trace, options.trace) {
+        trace
+            .finish()
+            .with_context(|| format!("Couldn't write trace to {}", path.display()))?;
+    }
+    let summary = if options.stats {
+        stats.to_string()
+    } else {
+        stats.kills().to_string()
+    };
+    if trace_to_stdout {
+        eprintln!("{}", summary);
+    } else {
+        println!("{}", summary);
+    }
+    if let Some(path) = options.stats_csv {
+        let file =
+            File::create(&path).with_context(|| format!("Couldn't create {}", path.display()))?;
+        stats
+            .write_csv(BufWriter::new(file))
+            .with_context(|| format!("Couldn't write statistics to {}", path.display()))?;
     }
 
-    let mut winner: &str = "Draw";
-    let mut winner_score = 0;
-    for (contender, score) in results {
-        if score > winner_score {
-            winner = contender;
-            winner_score = score;
+    Ok(())
+}
+
+fn save_replay(path: &Path, core: &Core, outcome: &MatchOutcome) -> Result<()> {
+    Replay::new(core, outcome)?
+        .save(path)
+        .with_context(|| format!("Couldn't save replay to {}", path.display()))
+}
+
+fn run_replay(path: &Path, with_visualiser: bool) -> Result<()> {
+    let replay =
+        Replay::load(path).with_context(|| format!("Couldn't load replay {}", path.display()))?;
+    let mut builder = replay.builder()?;
+
+    if with_visualiser {
+        builder.track_ownership(true);
+        return visual::run_with_visualiser(replay.build(&builder)?);
+    }
+
+    let outcome = replay.run()?;
+    println!("Replay matched the recording: {}", outcome);
+
+    Ok(())
+}
+
+fn run_heatmap(
+    builder: &CoreBuilder,
+    output: &Path,
+    rounds: usize,
+    activity: Activity,
+    scale: usize,
+) -> Result<()> {
+    let mut heatmap = Heatmap::new();
+    for _ in 0..rounds {
+        let mut core = builder.build()?;
+        core.log_with(&mut heatmap).run();
+    }
+
+    heatmap
+        .render(activity, Layout::square(heatmap.core_size(), scale))
+        .save(output)
+        .with_context(|| format!("Couldn't write heatmap to {}", output.display()))?;
+
+    Ok(())
+}
+
+fn run_classify(
+    builder: &CoreBuilder,
+    warrior: &Path,
+    cycles: usize,
+    json: bool,
+    defines: &Defines,
+) -> Result<()> {
+    let mut builder = builder.clone();
+    builder.load_warriors(&load_warriors(&[warrior], defines)?)?;
+    let classification = Classifier::new().cycles(cycles).classify(&builder)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&classification)?);
+    } else {
+        println!("{}", classification);
+    }
+
+    Ok(())
+}
+
+fn run_animate(
+    builder: &CoreBuilder,
+    output: &Path,
+    seed: u64,
+    interval: usize,
+    delay: u16,
+    scale: usize,
+    max_frames: usize,
+) -> Result<()> {
+    let mut core = builder.build_with_seed(seed)?;
+    let layout = Layout::square(core.instructions().len(), scale);
+    let mut animation = Animation::new(interval, layout);
+    animation.max_frames(max_frames);
+    core.log_with(&mut animation).run();
+    drop(core);
+
+    if output.extension().is_some_and(|ext| ext == "gif") {
+        let file = File::create(output)
+            .with_context(|| format!("Couldn't create {}", output.display()))?;
+        animation
+            .write_gif(BufWriter::new(file), delay)
+            .with_context(|| format!("Couldn't write animation to {}", output.display()))?;
+    } else {
+        animation
+            .save_frames(output)
+            .with_context(|| format!("Couldn't write frames to {}", output.display()))?;
+    }
+    println!(
+        "Wrote {} frames of the round with seed {}",
+        animation.frames().len(),
+        seed
+    );
+    if animation.truncated() {
+        println!(
+            "Stopped drawing after {} frames, raise --max-frames or --interval to see the whole round",
+            max_frames
+        );
+    }
+
+    Ok(())
+}
+
+fn run_debug(builder: &CoreBuilder, sources: &[String], seed: u64) -> Result<()> {
+    let mut debugger = Debugger::new(builder.build_with_seed(seed)?);
+    for (i, path) in sources.iter().enumerate() {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read warrior {}", path))?;
+        debugger.labels(i, &Warrior::labels(&source)?);
+    }
+
+    println!("Round with seed {}, type help for commands", seed);
+    println!("{}", debugger.execute(&DebugCommand::Step(0))?);
+    let stdin = io::stdin();
+    loop {
+        print!("(mars) ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+
+        match line.parse::<DebugCommand>() {
+            Ok(DebugCommand::Quit) => break,
+            Ok(command) => match debugger.execute(&command) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("{}", e),
+            },
+            Err(e) => println!("{}", e),
         }
     }
 
-    String::from(winner)
+    Ok(())
 }
 
-fn run_many<'a>(cores: &'a mut [Core]) -> Vec<MatchOutcome<'a>> {
-    let length = cores.len() as u64;
-    cores
-        .par_iter_mut()
-        .progress_count(length)
-        .map(|core| core.run())
-        .collect()
+/// Loads every .red file in `directory`, in order of file name.
+fn load_directory(directory: &Path) -> Result<Vec<Warrior>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(directory)
+        .with_context(|| format!("Couldn't read directory {}", directory.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "red") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    load_warriors(&paths, &Defines::default())
 }
 
-fn run_many_single_threaded<'a>(cores: &'a mut [Core]) -> Vec<MatchOutcome<'a>> {
-    let length = cores.len() as u64;
-    cores
-        .iter_mut()
-        .progress_count(length)
-        .map(|core| core.run())
-        .collect()
+fn run_tournament(
+    builder: &CoreBuilder,
+    directory: &Path,
+    rounds: usize,
+    self_play: bool,
+    both_orders: bool,
+    single_threaded: bool,
+) -> Result<()> {
+    let warriors = load_directory(directory)?;
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded).show_progress(true);
+
+    let results = Tournament::new()
+        .rounds(rounds)
+        .self_play(self_play)
+        .both_orders(both_orders)
+        .runner(runner)
+        .run(builder, &warriors, &Standard::new())?;
+
+    println!("{}", results);
+
+    Ok(())
+}
+
+fn run_bench(
+    builder: &CoreBuilder,
+    warrior: &Path,
+    directory: &Path,
+    rounds: usize,
+    json: bool,
+    defines: &Defines,
+    single_threaded: bool,
+) -> Result<()> {
+    let candidate = load_warriors(&[warrior], defines)?.remove(0);
+    let opponents = load_directory(directory)?;
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded).show_progress(!json);
+
+    let report = Benchmark::new().rounds(rounds).runner(runner).run(
+        builder,
+        &candidate,
+        &opponents,
+        &Standard::new(),
+    )?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_sweep(
+    builder: &CoreBuilder,
+    warrior: &Path,
+    opponent: &Path,
+    (from, to): (Option<usize>, Option<usize>),
+    step: usize,
+    rounds: usize,
+    csv: Option<&Path>,
+    json: bool,
+    defines: &Defines,
+    single_threaded: bool,
+) -> Result<()> {
+    let warrior = load_warriors(&[warrior], defines)?.remove(0);
+    let opponent = load_warriors(&[opponent], &Defines::default())?.remove(0);
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded).show_progress(!json);
+    let mut sweep = Sweep::new();
+    sweep.step(step).rounds(rounds).runner(runner);
+    if let Some(from) = from {
+        sweep.from(from);
+    }
+    if let Some(to) = to {
+        sweep.to(to);
+    }
+
+    let report = sweep.run(builder, &warrior, &opponent)?;
+
+    if let Some(path) = csv {
+        let file =
+            File::create(path).with_context(|| format!("Couldn't create {}", path.display()))?;
+        report
+            .write_csv(BufWriter::new(file))
+            .with_context(|| format!("Couldn't write results to {}", path.display()))?;
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+
+    Ok(())
+}
+
+fn run_profile(
+    builder: &CoreBuilder,
+    warrior: &Path,
+    opponents: &[PathBuf],
+    rounds: usize,
+    defines: &Defines,
+) -> Result<()> {
+    let source = std::fs::read_to_string(warrior)
+        .with_context(|| format!("Couldn't read {}", warrior.display()))?;
+    let mut warriors = load_warriors(&[warrior], defines)?;
+    warriors.extend(load_warriors(opponents, &Defines::default())?);
+    let mut builder = builder.clone();
+    builder.load_warriors(&warriors)?;
+
+    let mut profiler = Profiler::new(0);
+    for _ in 0..rounds {
+        let mut core = builder.build()?;
+        core.log_with(&mut profiler).run();
+    }
+
+    println!("{}", profiler.annotate(&source)?);
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_compare(
+    builder: &CoreBuilder,
+    baseline: &Path,
+    candidate: &Path,
+    directory: &Path,
+    rounds: usize,
+    seed: Option<u64>,
+    json: bool,
+    defines: &Defines,
+    single_threaded: bool,
+) -> Result<()> {
+    let versions = load_warriors(&[baseline, candidate], defines)?;
+    let opponents = load_directory(directory)?;
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded).show_progress(!json);
+
+    let mut comparison = Comparison::new();
+    comparison.rounds(rounds).runner(runner);
+    if let Some(seed) = seed {
+        comparison.seed(seed);
+    }
+    let report = comparison.run(
+        builder,
+        &versions[0],
+        &versions[1],
+        &opponents,
+        &Standard::new(),
+    )?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_evolve(
+    builder: &CoreBuilder,
+    output: &Path,
+    benchmark: Option<&Path>,
+    seeds: Option<&Path>,
+    generations: usize,
+    population: usize,
+    rounds: usize,
+    checkpoint_every: usize,
+    single_threaded: bool,
+) -> Result<()> {
+    let fitness = match benchmark {
+        Some(directory) => Fitness::Benchmark(load_directory(directory)?),
+        None => Fitness::RoundRobin,
+    };
+    let seeds = match seeds {
+        Some(directory) => load_directory(directory)?,
+        None => Vec::new(),
+    };
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded);
+
+    let mut evolver = Evolver::new(builder.clone(), fitness);
+    evolver.rounds(rounds).runner(runner);
+
+    let mut rng = rand::thread_rng();
+    let initial = evolver.initial_population(&seeds, population, &mut rng);
+
+    let mut checkpoint_error = None;
+    let last = evolver.evolve(initial, generations, &mut rng, |population| {
+        let fitnesses: Vec<f64> = population
+            .members()
+            .iter()
+            .filter_map(|m| m.fitness)
+            .collect();
+        println!(
+            "Generation {:>4}: best {:>6.1}, mean {:>6.1}",
+            population.generation(),
+            fitnesses.iter().cloned().fold(0.0, f64::max),
+            fitnesses.iter().sum::<f64>() / fitnesses.len().max(1) as f64
+        );
+
+        if checkpoint_every > 0 && population.generation() % checkpoint_every == 0 {
+            if let Err(e) = population.checkpoint(output) {
+                checkpoint_error.get_or_insert(e);
+            }
+        }
+    })?;
+    if let Some(e) = checkpoint_error {
+        return Err(e).with_context(|| format!("Couldn't save population to {}", output.display()));
+    }
+
+    last.checkpoint(output)
+        .with_context(|| format!("Couldn't save population to {}", output.display()))?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_optimise(
+    builder: &CoreBuilder,
+    warrior: &Path,
+    directory: &Path,
+    strategy: &str,
+    samples: usize,
+    restarts: usize,
+    max_candidates: usize,
+    rounds: usize,
+    top: usize,
+    defines: &Defines,
+    single_threaded: bool,
+) -> Result<()> {
+    let strategy = match strategy {
+        "grid" => Strategy::Grid,
+        "random" => Strategy::Random(samples),
+        "hill-climb" => Strategy::HillClimb(restarts),
+        _ => anyhow::bail!(
+            "Unknown strategy {}, expected grid, random or hill-climb",
+            strategy
+        ),
+    };
+    let source = std::fs::read_to_string(warrior)
+        .with_context(|| format!("Couldn't read {}", warrior.display()))?;
+    let opponents = load_directory(directory)?;
+
+    let mut runner = Runner::new();
+    runner.single_threaded(single_threaded).show_progress(true);
+
+    let mut results = Optimiser::new()
+        .rounds(rounds)
+        .strategy(strategy)
+        .max_candidates(max_candidates)
+        .overrides(defines.values.clone(), defines.force)
+        .runner(runner)
+        .run(builder, &source, &opponents, &mut rand::thread_rng())?;
+
+    let tried = results.candidates().len();
+    results.truncate(top);
+    println!("{}", results);
+    println!("{} candidates tried", tried);
+
+    Ok(())
+}
+
+fn run_hill(file: &Path, action: HillAction, single_threaded: bool) -> Result<()> {
+    let load =
+        || Hill::load(file).with_context(|| format!("Couldn't load hill {}", file.display()));
+
+    match action {
+        HillAction::Init {
+            preset,
+            size,
+            rounds,
+        } => {
+            let hill = Hill::new(preset, size, rounds);
+            hill.save(file)
+                .with_context(|| format!("Couldn't save hill {}", file.display()))?;
+            println!("{}", hill);
+        }
+        HillAction::Challenge { warrior } => {
+            let mut hill = load()?;
+            let source = std::fs::read_to_string(&warrior)
+                .with_context(|| format!("Couldn't read {}", warrior.display()))?;
+
+            let mut runner = Runner::new();
+            runner.single_threaded(single_threaded).show_progress(true);
+            let outcome = hill.challenge(&source, &runner)?;
+            hill.save(file)
+                .with_context(|| format!("Couldn't save hill {}", file.display()))?;
+
+            match outcome.rank {
+                Some(rank) => println!("Challenger placed {} of {}", rank, hill.warriors().len()),
+                None => println!("Challenger failed to make the hill"),
+            }
+            if let Some(warrior) = outcome.pushed_off {
+                println!(
+                    "{} by {} was pushed off the hill",
+                    warrior.name(),
+                    warrior.author()
+                );
+            }
+            println!();
+            println!("{}", hill);
+        }
+        HillAction::Standings => println!("{}", load()?),
+    }
+
+    Ok(())
+}
+
+fn scoring_for(participants: &[Warrior]) -> Box<dyn Scoring> {
+    if participants.len() > 2 {
+        Box::new(Melee)
+    } else {
+        Box::new(Standard::new())
+    }
+}
+
+fn declare_results(rounds: &[RoundResult], participants: &[Warrior]) -> Results {
+    Results::from_rounds(scoring_for(participants).as_ref(), participants, rounds)
 }
 
 fn main() -> Result<(), Error> {
     let Opt {
+        command,
         warriors,
         core_size,
+        preset,
         matches,
         single_threaded,
+        precision,
+        batch_size,
+        min_matches,
+        max_matches,
         with_visualiser,
+        defines,
+        force_defines,
+        trace,
+        trace_cycles,
+        trace_warrior,
+        record,
+        seed,
+        stats,
+        stats_csv,
+        stats_interval,
     } = Opt::from_args();
+    let defines = Defines {
+        values: defines.into_iter().collect(),
+        force: force_defines,
+    };
 
     let mut builder = Core::builder();
+    if let Some(preset) = preset {
+        builder.preset(preset);
+    }
     if let Some(size) = core_size {
         builder.core_size(size);
     }
 
-    let warriors = load_warriors(warriors)?;
+    if let Some(command) = command {
+        return match command {
+            Command::Tournament {
+                directory,
+                rounds,
+                self_play,
+                both_orders,
+            } => run_tournament(
+                &builder,
+                &directory,
+                rounds,
+                self_play,
+                both_orders,
+                single_threaded,
+            ),
+            Command::Bench {
+                warrior,
+                directory,
+                rounds,
+                json,
+            } => run_bench(
+                &builder,
+                &warrior,
+                &directory,
+                rounds,
+                json,
+                &defines,
+                single_threaded,
+            ),
+            Command::Compare {
+                baseline,
+                candidate,
+                directory,
+                rounds,
+                seed,
+                json,
+            } => run_compare(
+                &builder,
+                &baseline,
+                &candidate,
+                &directory,
+                rounds,
+                seed,
+                json,
+                &defines,
+                single_threaded,
+            ),
+            Command::Sweep {
+                warrior,
+                opponent,
+                from,
+                to,
+                step,
+                rounds,
+                csv,
+                json,
+            } => run_sweep(
+                &builder,
+                &warrior,
+                &opponent,
+                (from, to),
+                step,
+                rounds,
+                csv.as_deref(),
+                json,
+                &defines,
+                single_threaded,
+            ),
+            Command::Profile {
+                warrior,
+                opponents,
+                rounds,
+            } => run_profile(&builder, &warrior, &opponents, rounds, &defines),
+            Command::Evolve {
+                output,
+                benchmark,
+                seeds,
+                generations,
+                population,
+                rounds,
+                checkpoint_every,
+            } => run_evolve(
+                &builder,
+                &output,
+                benchmark.as_deref(),
+                seeds.as_deref(),
+                generations,
+                population,
+                rounds,
+                checkpoint_every,
+                single_threaded,
+            ),
+            Command::Optimise {
+                warrior,
+                directory,
+                strategy,
+                samples,
+                restarts,
+                max_candidates,
+                rounds,
+                top,
+            } => run_optimise(
+                &builder,
+                &warrior,
+                &directory,
+                &strategy,
+                samples,
+                restarts,
+                max_candidates,
+                rounds,
+                top,
+                &defines,
+                single_threaded,
+            ),
+            Command::Hill { file, action } => run_hill(&file, action, single_threaded),
+            Command::Replay {
+                file,
+                with_visualiser,
+            } => run_replay(&file, with_visualiser),
+            Command::Heatmap {
+                output,
+                rounds,
+                activity,
+                scale,
+            } => run_heatmap(
+                builder.load_warriors(&load_warriors(&warriors, &defines)?)?,
+                &output,
+                rounds,
+                activity,
+                scale,
+            ),
+            Command::Classify {
+                warrior,
+                cycles,
+                json,
+            } => run_classify(&builder, &warrior, cycles, json, &defines),
+            Command::Animate {
+                output,
+                interval,
+                delay,
+                scale,
+                max_frames,
+            } => run_animate(
+                builder
+                    .track_ownership(true)
+                    .load_warriors(&load_warriors(&warriors, &defines)?)?,
+                &output,
+                seed.unwrap_or_else(rand::random),
+                interval,
+                delay,
+                scale,
+                max_frames,
+            ),
+            Command::Debug => run_debug(
+                builder.load_warriors(&load_warriors(&warriors, &defines)?)?,
+                &warriors,
+                seed.unwrap_or_else(rand::random),
+            ),
+        };
+    }
+
+    let warriors = load_warriors(&warriors, &defines)?;
+
+    let single_match = !with_visualiser && precision.is_none() && matches == 1;
+    let observed = trace.is_some() || record.is_some() || stats || stats_csv.is_some();
+    if !single_match && (observed || seed.is_some()) {
+        anyhow::bail!(
+            "--trace, --record, --stats, --stats-csv and --seed can only be used with a single match"
+        );
+    }
 
     if with_visualiser {
-        let core = builder.load_warriors(&warriors)?.build()?;
-        visual::run_with_visualiser(core)?;
-    } else if matches == 1 {
-        let mut core = builder
+        let core = builder
+            .track_ownership(true)
             .load_warriors(&warriors)?
-            .log_with(Box::new(DebugLogger::new()))
             .build()?;
-
-        core.run();
-    } else {
+        visual::run_with_visualiser(core)?;
+    } else if let Some(target_width) = precision {
         let builder = builder.load_warriors(&warriors)?;
-
-        let cores: Result<Vec<Core>> = (0..matches)
-            .map(|_| {
-                let core = builder.build()?;
-                Ok(core)
-            })
-            .collect();
-
-        let mut cores = cores?;
-
-        let results = if !single_threaded {
-            run_many(&mut cores)
-        } else {
-            run_many_single_threaded(&mut cores)
+        let settings = Adaptive {
+            target_width,
+            batch_size,
+            min_rounds: min_matches,
+            max_rounds: max_matches,
         };
 
-        let match_count = results.len();
+        let results = Runner::new()
+            .single_threaded(single_threaded)
+            .show_progress(true)
+            .run_adaptive(builder, &settings, scoring_for(builder.warriors()).as_ref())?;
 
+        println!("{}", declare_results(&results.rounds, builder.warriors()));
         println!(
-            "The winner is {} after {} matches",
-            declare_results(results, &warriors),
-            match_count
+            "Score differences known to within ±{:.2}% (95% confidence){}",
+            results.width / 2.0,
+            if results.converged {
+                ""
+            } else {
+                ", maximum number of matches reached"
+            }
         );
+    } else if matches == 1 {
+        let options = SingleMatch {
+            seed: seed.unwrap_or_else(rand::random),
+            trace,
+            trace_cycles,
+            trace_warrior,
+            record,
+            stats,
+            stats_csv,
+            stats_interval,
+        };
+        // Ownership is needed to count the cells each warrior owns and to tell which warrior
+        // killed which.
+        builder.track_ownership(true);
+        run_single(builder.load_warriors(&warriors)?, options)?;
+    } else {
+        let builder = builder.load_warriors(&warriors)?;
+
+        let rounds = Runner::new()
+            .single_threaded(single_threaded)
+            .show_progress(true)
+            .run(builder, matches)?;
+
+        println!("{}", declare_results(&rounds, builder.warriors()));
     }
 
     Ok(())
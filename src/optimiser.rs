@@ -0,0 +1,551 @@
+//! Optimising the constants of a warrior, such as the step of a stone or a scanner.
+//!
+//! Constants are marked as tunable by ending their EQU definition with a `;tune` comment giving
+//! the range of values to try, and optionally the step between them:
+//!
+//! ```redcode
+//! step    EQU     3044        ;tune 4..CORESIZE by 4
+//! ```
+//!
+//! `CORESIZE` stands for the size of the core the warrior is optimised for. Candidate values are
+//! scored by fighting the warrior against a set of opponents, and the way candidates are chosen
+//! is set by a [`Strategy`](enum.Strategy.html).
+use crate::{
+    core::CoreBuilder, error::OptimiseError, runner::Runner, score::Standard,
+    tournament::Tournament, warrior::Warrior,
+};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// A constant marked as tunable, along with the values it can take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tunable {
+    /// The name of the constant.
+    pub name: String,
+    /// The value the constant is defined as, if it's a plain number.
+    pub default: Option<i64>,
+    /// The smallest value to try.
+    pub min: i64,
+    /// The largest value to try.
+    pub max: i64,
+    /// The difference between consecutive values to try.
+    pub step: i64,
+}
+
+impl Tunable {
+    /// The number of values the constant can take.
+    pub fn len(&self) -> usize {
+        if self.max < self.min {
+            0
+        } else {
+            ((self.max - self.min) / self.step + 1) as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `n`th value the constant can take.
+    pub fn value(&self, n: usize) -> i64 {
+        self.min + n as i64 * self.step
+    }
+
+    /// The position of the value closest to `value` among the values the constant can take.
+    fn position(&self, value: i64) -> usize {
+        let n = ((value - self.min) as f64 / self.step as f64).round();
+        (n.max(0.0) as usize).min(self.len().saturating_sub(1))
+    }
+}
+
+/// Finds the constants in `source` marked as tunable. `CORESIZE` in a range is replaced by
+/// `core_size`.
+pub fn tunables(source: &str, core_size: usize) -> Result<Vec<Tunable>, OptimiseError> {
+    let mut tunables = Vec::new();
+
+    for line in source.lines() {
+        let (code, comment) = match line.find(';') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => continue,
+        };
+        let marker = match comment.strip_prefix("tune") {
+            Some(marker) if marker.is_empty() || marker.starts_with(char::is_whitespace) => {
+                marker.trim()
+            }
+            _ => continue,
+        };
+
+        let words: Vec<&str> = code.split_whitespace().collect();
+        if words.len() < 3 || !words[1].eq_ignore_ascii_case("EQU") {
+            continue;
+        }
+        let name = words[0].to_owned();
+        let bad_marker = || OptimiseError::BadMarker(name.clone(), marker.to_owned());
+
+        let number = |s: &str| -> Result<i64, OptimiseError> {
+            if s.eq_ignore_ascii_case("CORESIZE") {
+                Ok(core_size as i64)
+            } else {
+                s.parse().map_err(|_| bad_marker())
+            }
+        };
+
+        let (range, step) = match marker.split_once(" by ") {
+            Some((range, step)) => (range.trim(), number(step.trim())?),
+            None => (marker, 1),
+        };
+        let (min, max) = range.split_once("..").ok_or_else(bad_marker)?;
+        let (min, max) = (number(min.trim())?, number(max.trim())?);
+        if step <= 0 || max < min {
+            return Err(bad_marker());
+        }
+
+        tunables.push(Tunable {
+            default: words[2..].join(" ").parse().ok(),
+            name,
+            min,
+            max,
+            step,
+        });
+    }
+
+    if tunables.is_empty() {
+        return Err(OptimiseError::NoTunables);
+    }
+
+    Ok(tunables)
+}
+
+/// How candidate values are chosen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    /// Try every combination of values, as long as there are no more than the optimiser's
+    /// maximum number of candidates.
+    Grid,
+    /// Try this many combinations of values, chosen at random.
+    Random(usize),
+    /// Start from the defined values, or random ones, and repeatedly move to the best
+    /// neighbouring combination of values until none is better, restarting from random values
+    /// this many times.
+    HillClimb(usize),
+}
+
+/// A combination of values for the tunable constants, along with its score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// The value of each tunable constant, in the order they are defined.
+    pub values: Vec<i64>,
+    /// The warrior's average score against the opponents with these values, in points per 100
+    /// rounds.
+    pub score: f64,
+}
+
+/// Searches for the values of a warrior's tunable constants that score best against a set of
+/// opponents.
+#[derive(Debug, Clone)]
+pub struct Optimiser {
+    rounds: usize,
+    strategy: Strategy,
+    max_candidates: usize,
+    overrides: HashMap<String, String>,
+    force_overrides: bool,
+    runner: Runner,
+}
+
+impl Default for Optimiser {
+    fn default() -> Self {
+        Self {
+            rounds: 100,
+            strategy: Strategy::HillClimb(3),
+            max_candidates: 10_000,
+            overrides: HashMap::new(),
+            force_overrides: false,
+            runner: Runner::new(),
+        }
+    }
+}
+
+impl Optimiser {
+    /// Creates a new optimiser, which hill-climbs with 3 restarts, fights each candidate
+    /// against each opponent for 100 rounds, and tries at most 10,000 candidates in a grid
+    /// search.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of rounds each candidate fights against each opponent.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Sets the strategy used to choose candidates.
+    pub fn strategy(&mut self, strategy: Strategy) -> &mut Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the largest number of candidates a grid search will try. A grid with more
+    /// combinations of values than this is refused rather than run.
+    pub fn max_candidates(&mut self, max_candidates: usize) -> &mut Self {
+        self.max_candidates = max_candidates;
+        self
+    }
+
+    /// Sets fixed values for EQU constants that aren't being tuned, as in
+    /// [`Warrior::parse_with_overrides`](../warrior/struct.Warrior.html#method.parse_with_overrides).
+    /// Overrides of tunable constants are ignored.
+    pub fn overrides(&mut self, overrides: HashMap<String, String>, force: bool) -> &mut Self {
+        self.overrides = overrides;
+        self.force_overrides = force;
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Optimises the tunable constants of the warrior in `source` against `opponents`, using the
+    /// settings in `builder` for every round. Any warriors already loaded into `builder` are
+    /// ignored.
+    pub fn run<R: Rng>(
+        &self,
+        builder: &CoreBuilder,
+        source: &str,
+        opponents: &[Warrior],
+        rng: &mut R,
+    ) -> Result<OptimiserResults, OptimiseError> {
+        let tunables = tunables(source, builder.core_size)?;
+        let mut search = Search {
+            optimiser: self,
+            builder,
+            source,
+            opponents,
+            tunables: &tunables,
+            scores: HashMap::new(),
+        };
+
+        match self.strategy {
+            Strategy::Grid => {
+                let size = tunables
+                    .iter()
+                    .try_fold(1usize, |size, t| size.checked_mul(t.len()))
+                    .unwrap_or(usize::MAX);
+                if size > self.max_candidates {
+                    return Err(OptimiseError::TooManyCandidates(size, self.max_candidates));
+                }
+
+                let mut candidates = vec![Vec::new()];
+                for tunable in &tunables {
+                    candidates = candidates
+                        .into_iter()
+                        .flat_map(|values: Vec<usize>| {
+                            (0..tunable.len()).map(move |n| {
+                                let mut values = values.clone();
+                                values.push(n);
+                                values
+                            })
+                        })
+                        .collect();
+                }
+                search.evaluate(&candidates)?;
+            }
+            Strategy::Random(samples) => {
+                let candidates: Vec<Vec<usize>> =
+                    (0..samples).map(|_| search.random(rng)).collect();
+                search.evaluate(&candidates)?;
+            }
+            Strategy::HillClimb(restarts) => {
+                let defaults: Vec<usize> = tunables
+                    .iter()
+                    .map(|t| match t.default {
+                        Some(value) => t.position(value),
+                        None => rng.gen_range(0, t.len()),
+                    })
+                    .collect();
+                search.climb(defaults)?;
+                for _ in 0..restarts {
+                    let start = search.random(rng);
+                    search.climb(start)?;
+                }
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = search
+            .scores
+            .into_iter()
+            .map(|(positions, score)| Candidate {
+                values: positions
+                    .iter()
+                    .zip(&tunables)
+                    .map(|(&n, t)| t.value(n))
+                    .collect(),
+                score,
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.values.cmp(&b.values))
+        });
+
+        Ok(OptimiserResults {
+            tunables,
+            candidates,
+        })
+    }
+}
+
+/// The state of a single optimisation, with every candidate scored so far, identified by the
+/// position of each of its values among those its constant can take.
+struct Search<'a> {
+    optimiser: &'a Optimiser,
+    builder: &'a CoreBuilder,
+    source: &'a str,
+    opponents: &'a [Warrior],
+    tunables: &'a [Tunable],
+    scores: HashMap<Vec<usize>, f64>,
+}
+
+impl Search<'_> {
+    fn random<R: Rng>(&self, rng: &mut R) -> Vec<usize> {
+        self.tunables
+            .iter()
+            .map(|t| rng.gen_range(0, t.len()))
+            .collect()
+    }
+
+    /// Scores every candidate that hasn't already been scored, in a single batch.
+    fn evaluate(&mut self, candidates: &[Vec<usize>]) -> Result<(), OptimiseError> {
+        let mut new: Vec<&Vec<usize>> = candidates
+            .iter()
+            .filter(|c| !self.scores.contains_key(*c))
+            .collect();
+        new.sort();
+        new.dedup();
+        if new.is_empty() {
+            return Ok(());
+        }
+
+        let mut warriors = new
+            .iter()
+            .map(|positions| {
+                let mut overrides = self.optimiser.overrides.clone();
+                overrides.extend(
+                    positions
+                        .iter()
+                        .zip(self.tunables)
+                        .map(|(&n, t)| (t.name.clone(), t.value(n).to_string())),
+                );
+                Warrior::parse_with_overrides(
+                    self.source,
+                    0,
+                    &overrides,
+                    self.optimiser.force_overrides,
+                )
+                .map_err(|e| OptimiseError::Warrior(format_values(self.tunables, positions), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let candidates = warriors.len();
+        warriors.extend_from_slice(self.opponents);
+
+        let pairings: Vec<(usize, usize)> = (0..candidates)
+            .flat_map(|a| (candidates..warriors.len()).map(move |b| (a, b)))
+            .collect();
+        let results = Tournament::new()
+            .rounds(self.optimiser.rounds)
+            .runner(self.optimiser.runner.clone())
+            .run_pairings(self.builder, &warriors, &pairings, &Standard::new())?;
+
+        for (i, positions) in new.into_iter().enumerate() {
+            self.scores.insert(positions.clone(), results.total(i));
+        }
+
+        Ok(())
+    }
+
+    /// Moves from `current` to its best neighbour until none of its neighbours score better.
+    /// A candidate's neighbours differ from it in a single value, by one step or by a tenth of
+    /// the constant's range.
+    fn climb(&mut self, mut current: Vec<usize>) -> Result<(), OptimiseError> {
+        self.evaluate(std::slice::from_ref(&current))?;
+
+        loop {
+            let mut neighbours = Vec::new();
+            for (i, tunable) in self.tunables.iter().enumerate() {
+                let jump = (tunable.len() / 10).max(1);
+                for &distance in &[1, jump] {
+                    if current[i] >= distance {
+                        let mut neighbour = current.clone();
+                        neighbour[i] -= distance;
+                        neighbours.push(neighbour);
+                    }
+                    if current[i] + distance < tunable.len() {
+                        let mut neighbour = current.clone();
+                        neighbour[i] += distance;
+                        neighbours.push(neighbour);
+                    }
+                }
+            }
+            self.evaluate(&neighbours)?;
+
+            let best = neighbours
+                .into_iter()
+                .max_by(|a, b| self.scores[a].total_cmp(&self.scores[b]));
+            match best {
+                Some(best) if self.scores[&best] > self.scores[&current] => current = best,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn format_values(tunables: &[Tunable], positions: &[usize]) -> String {
+    tunables
+        .iter()
+        .zip(positions)
+        .map(|(t, &n)| format!("{}={}", t.name, t.value(n)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The results of an [`Optimiser`](struct.Optimiser.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimiserResults {
+    tunables: Vec<Tunable>,
+    candidates: Vec<Candidate>,
+}
+
+impl OptimiserResults {
+    /// The tunable constants that were optimised.
+    pub fn tunables(&self) -> &[Tunable] {
+        &self.tunables
+    }
+
+    /// Every candidate that was scored, from best to worst.
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// The best candidate.
+    pub fn best(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// Keeps only the best `len` candidates.
+    pub fn truncate(&mut self, len: usize) {
+        self.candidates.truncate(len);
+    }
+}
+
+impl Display for OptimiserResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>4}", "Rank")?;
+        for tunable in &self.tunables {
+            write!(f, " {:>10}", tunable.name)?;
+        }
+        write!(f, " {:>8}", "Score")?;
+
+        for (rank, candidate) in self.candidates.iter().enumerate() {
+            write!(f, "\n{:>4}", rank + 1)?;
+            for value in &candidate.values {
+                write!(f, " {:>10}", value)?;
+            }
+            write!(f, " {:>8.1}", candidate.score)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const STONE: &str = "\
+;name Tunable stone
+step    EQU     3044        ;tune 4..CORESIZE by 4
+gap     EQU     2           ;tune 1..3
+        ORG     start
+start   mov.i   <gap, step
+        add.ab  #step, start
+        jmp     start
+        END
+";
+
+    #[test]
+    fn finds_tunables() {
+        let tunables = tunables(STONE, 8000).unwrap();
+        assert_eq!(
+            tunables,
+            vec![
+                Tunable {
+                    name: String::from("step"),
+                    default: Some(3044),
+                    min: 4,
+                    max: 8000,
+                    step: 4
+                },
+                Tunable {
+                    name: String::from("gap"),
+                    default: Some(2),
+                    min: 1,
+                    max: 3,
+                    step: 1
+                }
+            ]
+        );
+        assert_eq!(tunables[0].len(), 2000);
+        assert_eq!(tunables[0].position(3045), 760);
+
+        assert!(matches!(
+            super::tunables(include_str!("../warriors/dwarf.red"), 8000),
+            Err(OptimiseError::NoTunables)
+        ));
+        assert!(matches!(
+            super::tunables("x EQU 1 ;tune 5..1\n", 8000),
+            Err(OptimiseError::BadMarker(_, _))
+        ));
+        assert!(matches!(
+            super::tunables("x EQU 1 ;tuned by hand\n", 8000),
+            Err(OptimiseError::NoTunables)
+        ));
+    }
+
+    #[test]
+    fn strategies_score_candidates() {
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 0).unwrap();
+        let source = STONE.replace("4..CORESIZE by 4", "4..12 by 4");
+        let mut builder = CoreBuilder::new();
+        builder.cycles_before_tie(2000);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let results = Optimiser::new()
+            .rounds(2)
+            .strategy(Strategy::Grid)
+            .run(&builder, &source, std::slice::from_ref(&imp), &mut rng)
+            .unwrap();
+        assert_eq!(results.candidates().len(), 9);
+        let best = results.best().unwrap();
+        assert!(results.candidates().iter().all(|c| c.score <= best.score));
+
+        assert!(matches!(
+            Optimiser::new()
+                .strategy(Strategy::Grid)
+                .max_candidates(8)
+                .run(&builder, &source, std::slice::from_ref(&imp), &mut rng),
+            Err(OptimiseError::TooManyCandidates(9, 8))
+        ));
+
+        let results = Optimiser::new()
+            .rounds(2)
+            .strategy(Strategy::HillClimb(1))
+            .run(&builder, &source, &[imp], &mut rng)
+            .unwrap();
+        assert!(!results.candidates().is_empty());
+    }
+}
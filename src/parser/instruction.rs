@@ -11,6 +11,7 @@ use nom::{
     sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
 // Structs and Enums
@@ -44,7 +45,7 @@ pub struct Operation {
     pub modifier: Modifier,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AddressMode {
     Immediate,
     Direct,
@@ -76,7 +77,7 @@ impl Display for AddressMode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Modifier {
     A,
     B,
@@ -118,7 +119,7 @@ fn operation(i: &str) -> IResult<&str, Operation> {
     )(i)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Opcode {
     Dat,
     Mov,
@@ -179,7 +180,7 @@ impl Opcode {
     }
 }
 
-fn opcode(i: &str) -> IResult<&str, Opcode> {
+pub(crate) fn opcode(i: &str) -> IResult<&str, Opcode> {
     use tag_no_case as t;
     map(
         alt((
@@ -219,7 +220,7 @@ fn opcode(i: &str) -> IResult<&str, Opcode> {
             "SEQ" => Opcode::Seq,
             "SNE" => Opcode::Sne,
             "NOP" => Opcode::Nop,
-            _ => unreachable!(opcode),
+            _ => unreachable!("{}", opcode),
         },
     )(i)
 }
@@ -256,14 +257,14 @@ fn modifier(i: &str) -> IResult<&str, Modifier> {
     )(i)
 }
 
-fn address(i: &str) -> IResult<&str, Address> {
+fn address(i: &str) -> IResult<&str, Address<'_>> {
     map(pair(opt(address_mode), expr), |(mode, expr)| Address {
         mode: mode.unwrap_or(AddressMode::Direct),
         expr,
     })(i)
 }
 
-pub(super) fn instruction(i: &str) -> IResult<&str, RawInstruction> {
+pub(super) fn instruction(i: &str) -> IResult<&str, RawInstruction<'_>> {
     let (i, _) = space0(i)?;
     let (i, labels) = label_list(i)?;
     let (i, op) = operation(i)?;
@@ -315,7 +316,7 @@ pub(super) fn definition(i: &str) -> IResult<&str, (&str, &str, &str)> {
     Ok((i, (label, expression, full_definition)))
 }
 
-pub(super) fn org_statement(i: &str) -> IResult<&str, NumericExpr> {
+pub(super) fn org_statement(i: &str) -> IResult<&str, NumericExpr<'_>> {
     preceded(tuple((space0, tag_no_case("ORG"), space1)), expr)(i)
 }
 
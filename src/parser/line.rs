@@ -30,7 +30,7 @@ pub(crate) enum Line<'a> {
     },
 }
 
-fn line(i: &str) -> IResult<&str, Line> {
+fn line(i: &str) -> IResult<&str, Line<'_>> {
     terminated(
         delimited(
             space0,
@@ -53,17 +53,24 @@ fn line(i: &str) -> IResult<&str, Line> {
     )(i)
 }
 
-pub(crate) fn lines(i: &str) -> IResult<&str, Vec<Line>> {
+pub(crate) fn lines(i: &str) -> IResult<&str, Vec<Line<'_>>> {
+    map(located_lines, |list| {
+        list.into_iter().map(|(_, line)| line).collect()
+    })(i)
+}
+
+/// Like `lines`, but with each line paired with the offset into `i` it starts at.
+pub(crate) fn located_lines(i: &str) -> IResult<&str, Vec<(usize, Line<'_>)>> {
     all_consuming(preceded(
         multispace0,
         map(
             pair(
-                separated_list(tuple((space0, line_ending, multispace0)), line),
-                ending_line,
+                separated_list(tuple((space0, line_ending, multispace0)), located(i, line)),
+                located(i, ending_line),
             ),
-            |(mut list, end)| {
+            |(mut list, (offset, end))| {
                 if let Some(val) = end {
-                    list.push(Line::OrgStatement(val));
+                    list.push((offset, Line::OrgStatement(val)));
                 }
                 list
             },
@@ -71,7 +78,18 @@ pub(crate) fn lines(i: &str) -> IResult<&str, Vec<Line>> {
     ))(i)
 }
 
-fn ending_line(i: &str) -> IResult<&str, Option<NumericExpr>> {
+/// Runs `parser`, pairing what it parses with the offset into `input` it started at.
+fn located<'a, O>(
+    input: &'a str,
+    parser: impl Fn(&'a str) -> IResult<&'a str, O>,
+) -> impl Fn(&'a str) -> IResult<&'a str, (usize, O)> {
+    move |rest| {
+        let offset = input.len() - rest.len();
+        parser(rest).map(|(rest, parsed)| (rest, (offset, parsed)))
+    }
+}
+
+fn ending_line(i: &str) -> IResult<&str, Option<NumericExpr<'_>>> {
     delimited(
         multispace0,
         preceded(pair(tag_no_case("END"), space0), opt(expr)),
@@ -29,7 +29,7 @@ macro_rules! metadata_comment {
     };
 }
 
-pub(crate) fn metadata(i: &str) -> IResult<&str, MetadataValue> {
+pub(crate) fn metadata(i: &str) -> IResult<&str, MetadataValue<'_>> {
     alt((
         metadata_comment!("strategy", MetadataValue::Strategy),
         metadata_comment!("name", MetadataValue::Name),
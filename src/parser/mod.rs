@@ -1,23 +1,86 @@
 use crate::error::ParseError;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 pub(crate) mod instruction;
 pub(crate) mod line;
 pub(crate) mod metadata;
 pub(crate) mod numeric_expr;
 
-use line::{lines, Line};
+use line::{lines, located_lines, Line};
 
-pub(crate) fn parse(i: &str) -> Result<Vec<Line>, ParseError> {
-    let (_, ls) = lines(i).map_err(|e| match e {
+pub(crate) fn parse(i: &str) -> Result<Vec<Line<'_>>, ParseError> {
+    let ls = parse_located(i)?;
+
+    Ok(ls.into_iter().map(|(_, line)| line).collect())
+}
+
+/// Like `parse`, but with each line paired with the number of the line of `i` it is on, counting
+/// from 1.
+pub(crate) fn parse_located(i: &str) -> Result<Vec<(usize, Line<'_>)>, ParseError> {
+    let (_, ls) = located_lines(i).map_err(|e| match e {
         nom::Err::Incomplete(_) => ParseError::Incomplete,
         nom::Err::Error((_, ek)) | nom::Err::Failure((_, ek)) => ParseError::Parse(ek),
     })?;
 
-    Ok(ls)
+    let mut line_number = 1;
+    let mut counted = 0;
+    Ok(ls
+        .into_iter()
+        .map(|(offset, line)| {
+            line_number += i[counted..offset].matches('\n').count();
+            counted = offset;
+            (line_number, line)
+        })
+        .collect())
+}
+
+pub(crate) fn replace_definitions(s: &str) -> Result<Cow<'_, str>, ParseError> {
+    replace_definitions_with(s, &HashMap::new())
+}
+
+/// Like `replace_definitions`, but any definition named in `overrides` is replaced by the given
+/// value rather than by its definition in `s`. Overrides for names that `s` doesn't define are
+/// an error, unless `force` is set, in which case they are added as new definitions.
+pub(crate) fn override_definitions<'a>(
+    s: &'a str,
+    overrides: &HashMap<String, String>,
+    force: bool,
+) -> Result<Cow<'a, str>, ParseError> {
+    let (_, ls) = lines(s).map_err(|_| ParseError::Replace)?;
+    let mut undefined: Vec<&String> = overrides
+        .keys()
+        .filter(|name| {
+            !ls.iter()
+                .any(|line| matches!(line, Line::Definition { label, .. } if label == name))
+        })
+        .collect();
+    undefined.sort();
+
+    if undefined.is_empty() {
+        return replace_definitions_with(s, overrides);
+    }
+    if !force {
+        return Err(ParseError::UnknownOverride(undefined[0].clone()));
+    }
+
+    let mut injected = String::new();
+    for name in undefined {
+        injected.push_str(&format!("{} EQU {}\n", name, overrides[name]));
+    }
+    injected.push_str(s);
+
+    Ok(Cow::from(
+        replace_definitions_with(&injected, overrides)?.into_owned(),
+    ))
 }
 
-pub(crate) fn replace_definitions(s: &str) -> Result<Cow<str>, ParseError> {
+/// Replaces each use of a definition in `s` with its value. Overridden values are wrapped in
+/// parentheses, so that an expression given as an override is evaluated on its own.
+fn replace_definitions_with<'a>(
+    s: &'a str,
+    overrides: &HashMap<String, String>,
+) -> Result<Cow<'a, str>, ParseError> {
     let mut val = Cow::from(s);
     let (_, ls) = lines(s).map_err(|_| ParseError::Replace)?;
 
@@ -28,17 +91,48 @@ pub(crate) fn replace_definitions(s: &str) -> Result<Cow<str>, ParseError> {
             full_definition,
         } = line
         {
-            val = Cow::from(
-                val.to_mut()
-                    .replace(full_definition, "")
-                    .replace(label, definition.trim()),
-            );
+            let definition = match overrides.get(label) {
+                Some(value) => format!("({})", value.trim()),
+                None => definition.trim().to_owned(),
+            };
+            val = Cow::from(replace_label(
+                &val.replace(full_definition, ""),
+                label,
+                &definition,
+            ));
         }
     }
 
     Ok(val)
 }
 
+/// Replaces every use of `label` as a whole identifier in `s` with `value`, leaving comments,
+/// and labels that merely contain `label`, alone.
+fn replace_label(s: &str, label: &str, value: &str) -> String {
+    let is_identifier = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut replaced = String::with_capacity(s.len());
+
+    for line in s.split_inclusive('\n') {
+        let (code, comment) = line.split_at(line.find(';').unwrap_or(line.len()));
+        let mut copied = 0;
+        for (start, _) in code.match_indices(label) {
+            let end = start + label.len();
+            if is_identifier(code[..start].chars().next_back())
+                || is_identifier(code[end..].chars().next())
+            {
+                continue;
+            }
+            replaced.push_str(&code[copied..start]);
+            replaced.push_str(value);
+            copied = end;
+        }
+        replaced.push_str(&code[copied..]);
+        replaced.push_str(comment);
+    }
+
+    replaced
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -50,20 +144,66 @@ mod test {
             replaced,
             warrior
                 .replace("step    EQU      4                 ", "")
-                .replace("step", "4")
+                .replace("#step,", "#4,")
         );
         lines(&replaced).unwrap();
     }
 
+    #[test]
+    fn override_definitions_with_values() {
+        let warrior = include_str!("../../warriors/dwarf.red");
+        let mut overrides = HashMap::new();
+        overrides.insert(String::from("step"), String::from("3044"));
+
+        let replaced = override_definitions(warrior, &overrides, false).unwrap();
+        assert_eq!(
+            replaced,
+            warrior
+                .replace("step    EQU      4                 ", "")
+                .replace("#step,", "#(3044),")
+        );
+
+        overrides.insert(String::from("gap"), String::from("2"));
+        assert!(matches!(
+            override_definitions(warrior, &overrides, false),
+            Err(ParseError::UnknownOverride(name)) if name == "gap"
+        ));
+        let forced = override_definitions(warrior, &overrides, true).unwrap();
+        assert!(forced.contains("#(3044),") && !forced.contains("#step"));
+    }
+
+    #[test]
+    fn override_definitions_as_whole_identifiers() {
+        let warrior = "\
+;name step stone
+step    EQU     4
+stepper MOV.I   $step*2, $stepper ; moves by step
+        END
+";
+        let mut overrides = HashMap::new();
+        overrides.insert(String::from("step"), String::from("1+2"));
+
+        let replaced = override_definitions(warrior, &overrides, false).unwrap();
+        assert_eq!(
+            replaced,
+            "\
+;name step stone
+
+stepper MOV.I   $(1+2)*2, $stepper ; moves by step
+        END
+"
+        );
+    }
+
     #[test]
     fn test_bad_dwarf() {
         let warrior = include_str!("../../warriors/bad_dwarf.red");
-        lines(&warrior).unwrap_err();
+        lines(warrior).unwrap_err();
     }
 
     #[test]
     fn test_one_line_dwarf() {
         let warrior = include_str!("../../warriors/one_line_dwarf.red");
-        lines(&warrior).unwrap_err();
+        lines(warrior).unwrap_err();
     }
 }
@@ -138,7 +138,7 @@ fn number(i: &str) -> IResult<&str, i64> {
     })(i)
 }
 
-fn parens(i: &str) -> IResult<&str, NumericExpr> {
+fn parens(i: &str) -> IResult<&str, NumericExpr<'_>> {
     delimited(
         space0,
         delimited(
@@ -150,7 +150,7 @@ fn parens(i: &str) -> IResult<&str, NumericExpr> {
     )(i)
 }
 
-fn factor(i: &str) -> IResult<&str, NumericExpr> {
+fn factor(i: &str) -> IResult<&str, NumericExpr<'_>> {
     alt((
         map(delimited(space0, number, space0), |v| {
             NumericExpr::Value(ExprValue::Number(v))
@@ -178,7 +178,7 @@ fn fold_exprs<'a>(
     })
 }
 
-fn term(i: &str) -> IResult<&str, NumericExpr> {
+fn term(i: &str) -> IResult<&str, NumericExpr<'_>> {
     let (i, initial) = factor(i)?;
     let (i, remainder) = many0(alt((
         |i| {
@@ -198,7 +198,7 @@ fn term(i: &str) -> IResult<&str, NumericExpr> {
     Ok((i, fold_exprs(initial, remainder)))
 }
 
-pub(crate) fn expr(i: &str) -> IResult<&str, NumericExpr> {
+pub(crate) fn expr(i: &str) -> IResult<&str, NumericExpr<'_>> {
     let (i, initial) = term(i)?;
     let (i, remainder) = many0(alt((
         |i| {
@@ -0,0 +1,169 @@
+//! Finding where a warrior spends its cycles.
+//!
+//! A [`Profiler`](struct.Profiler.html) counts how many times each of a warrior's instructions is
+//! executed from where the warrior was loaded, over as many rounds as it is attached to. The
+//! counts can then be printed alongside the warrior's source, using
+//! [`Warrior::source_lines`](../warrior/struct.Warrior.html#method.source_lines) to find the line
+//! each instruction came from.
+use crate::{
+    core::Core,
+    error::Error,
+    logger::{GameEvent, Logger},
+    warrior::Warrior,
+};
+use std::fmt::Write;
+
+/// A `Logger` that counts the instructions a single warrior executes, by their position in the
+/// warrior's code.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    warrior: usize,
+    counts: Vec<u64>,
+    /// Instructions executed anywhere other than where the warrior was loaded, such as copies
+    /// of its code.
+    elsewhere: u64,
+    rounds: usize,
+}
+
+impl Profiler {
+    /// Creates a profiler for the warrior with index `warrior`, which is its position in the
+    /// order the warriors were loaded.
+    pub fn new(warrior: usize) -> Self {
+        Self {
+            warrior,
+            counts: Vec::new(),
+            elsewhere: 0,
+            rounds: 0,
+        }
+    }
+
+    /// The number of times each of the warrior's instructions was executed where it was loaded,
+    /// in the order they were loaded.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The number of instructions the warrior executed outside the code it was loaded as.
+    pub fn elsewhere(&self) -> u64 {
+        self.elsewhere
+    }
+
+    /// The number of instructions the warrior executed in total.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum::<u64>() + self.elsewhere
+    }
+
+    /// The number of rounds that have finished while profiling.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Prints `source`, the Redcode of the warrior profiled, with the number of times each
+    /// instruction was executed, and the share of all the instructions executed that it makes
+    /// up, next to the line it is on.
+    pub fn annotate(&self, source: &str) -> Result<String, Error> {
+        let lines = Warrior::source_lines(source)?;
+        let total = self.total().max(1) as f64;
+        let mut annotated = String::new();
+
+        let mut instructions = lines.iter().zip(&self.counts).peekable();
+        for (number, line) in source.lines().enumerate() {
+            match instructions.peek() {
+                Some((line_number, count)) if **line_number == number + 1 => {
+                    writeln!(
+                        annotated,
+                        "{:>10} {:>6.2}% | {}",
+                        count,
+                        **count as f64 / total * 100.0,
+                        line
+                    )
+                    .unwrap();
+                    instructions.next();
+                }
+                _ => writeln!(annotated, "{:>18} | {}", "", line).unwrap(),
+            }
+        }
+
+        writeln!(annotated).unwrap();
+        writeln!(
+            annotated,
+            "{:>10} {:>6.2}%   executed elsewhere",
+            self.elsewhere,
+            self.elsewhere as f64 / total * 100.0
+        )
+        .unwrap();
+        write!(
+            annotated,
+            "{:>10} {:>6}    executed in total over {} round{}",
+            self.total(),
+            "",
+            self.rounds,
+            if self.rounds == 1 { "" } else { "s" }
+        )
+        .unwrap();
+
+        Ok(annotated)
+    }
+}
+
+impl Logger for Profiler {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        match event {
+            GameEvent::Executed(execution) if execution.warrior.idx == self.warrior => {
+                let warrior = execution.warrior;
+                let size = current_game_state.instructions().len();
+                if self.counts.is_empty() {
+                    self.counts = vec![0; warrior.len()];
+                }
+                let start = current_game_state.loaded_at()[warrior.idx];
+
+                match self.counts.get_mut((execution.pc + size - start) % size) {
+                    Some(count) => *count += 1,
+                    None => self.elsewhere += 1,
+                }
+            }
+            GameEvent::GameOver(_) => self.rounds += 1,
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.warrior);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{CoreBuilder, Separation};
+
+    #[test]
+    fn profiles_source_lines() {
+        let source = include_str!("../warriors/dwarf.red");
+        let dwarf = Warrior::parse(source, 0).unwrap();
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(600)
+            .separation(Separation::Fixed(4000))
+            .load_warriors(&[dwarf, imp])
+            .unwrap();
+        let mut profiler = Profiler::new(0);
+        for _ in 0..2 {
+            let mut core = builder.build().unwrap();
+            core.log_with(&mut profiler).run();
+        }
+
+        // The dwarf's three instruction loop runs 100 times a round, far from the imp.
+        assert_eq!(profiler.rounds(), 2);
+        assert_eq!(profiler.counts()[0], 0);
+        assert_eq!(profiler.total(), 600);
+        let annotated = profiler.annotate(source).unwrap();
+        let lines: Vec<&str> = annotated.lines().collect();
+        assert!(lines[16].starts_with("         0   0.00% | target"));
+        assert!(lines[17].starts_with("       200  33.33% | start"));
+        assert!(lines[0].ends_with("| ;redcode"));
+        assert!(lines.last().unwrap().contains("over 2 rounds"));
+    }
+}
@@ -0,0 +1,236 @@
+use super::{warrior_colour, Image, Layout};
+use crate::{
+    core::Core,
+    error::RenderError,
+    logger::{GameEvent, Logger},
+};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A `Logger` that draws a frame of a round every `interval` cycles, and another when the round
+/// ends, so the round can be saved as an animation.
+///
+/// Each frame shows the cells owned by each warrior in a dim version of its colour, and the
+/// cells its processes are about to execute in its full colour. Cells are only coloured by owner
+/// if the core tracks ownership, see
+/// [`CoreBuilder::track_ownership`](../core/struct.CoreBuilder.html#method.track_ownership).
+///
+/// Frames are kept in memory until they are written, so at most
+/// [`max_frames`](#method.max_frames) are drawn. Once that many have been drawn, frames are
+/// skipped, apart from the one for the end of the round, which replaces the last frame.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    interval: usize,
+    layout: Layout,
+    max_frames: usize,
+    frames: Vec<Image>,
+    truncated: bool,
+}
+
+impl Animation {
+    /// Creates an animation with a frame every `interval` cycles, drawn with `layout`, and at
+    /// most 1000 frames.
+    pub fn new(interval: usize, layout: Layout) -> Self {
+        Self {
+            interval: interval.max(1),
+            layout,
+            max_frames: 1000,
+            frames: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    /// Sets the largest number of frames to draw, which is at least 1.
+    pub fn max_frames(&mut self, max_frames: usize) -> &mut Self {
+        self.max_frames = max_frames.max(1);
+        self
+    }
+
+    /// The frames drawn so far, in order.
+    pub fn frames(&self) -> &[Image] {
+        &self.frames
+    }
+
+    /// Whether any frames were skipped because the maximum number had already been drawn.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Writes the frames as an animated GIF which loops forever, showing each frame for `delay`
+    /// hundredths of a second.
+    pub fn write_gif<W: Write>(&self, writer: W, delay: u16) -> Result<(), RenderError> {
+        let first = match self.frames.first() {
+            Some(first) => first,
+            None => return Err(RenderError::Empty),
+        };
+        let (width, height) = (first.width(), first.height());
+        if width > usize::from(u16::MAX) || height > usize::from(u16::MAX) {
+            return Err(RenderError::TooLarge(width, height));
+        }
+
+        // Frames only use a handful of colours, so they can share one palette exactly rather
+        // than each being quantised.
+        let mut colours = HashMap::new();
+        for frame in &self.frames {
+            for pixel in frame.pixels().chunks(3) {
+                let next = colours.len();
+                colours
+                    .entry([pixel[0], pixel[1], pixel[2]])
+                    .or_insert(next);
+            }
+        }
+        if colours.len() > 256 {
+            return Err(RenderError::TooManyColours(colours.len()));
+        }
+        let mut palette = vec![0; colours.len() * 3];
+        for (colour, index) in &colours {
+            palette[index * 3..index * 3 + 3].copy_from_slice(colour);
+        }
+
+        let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for image in &self.frames {
+            let indices: Vec<u8> = image
+                .pixels()
+                .chunks(3)
+                .map(|pixel| colours[&[pixel[0], pixel[1], pixel[2]]] as u8)
+                .collect();
+            let mut frame =
+                gif::Frame::from_indexed_pixels(width as u16, height as u16, indices, None);
+            frame.delay = delay;
+            encoder.write_frame(&frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the frames as PNGs numbered from 0 in `directory`, creating it if needed, and
+    /// returns their paths.
+    pub fn save_frames(&self, directory: &Path) -> Result<Vec<PathBuf>, RenderError> {
+        fs::create_dir_all(directory)?;
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let path = directory.join(format!("frame-{:05}.png", i));
+                frame.write_png(BufWriter::new(File::create(&path)?))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    fn draw(&mut self, core: &Core) -> Image {
+        let mut image = Image::for_core(core.instructions().len(), self.layout);
+        for (address, owner) in core.owners().unwrap_or_default().iter().enumerate() {
+            if let Some(owner) = owner {
+                let [r, g, b] = warrior_colour(owner.warrior);
+                image.fill_cell(address, self.layout, [r / 3, g / 3, b / 3]);
+            }
+        }
+        for (warrior, queue) in core.task_queues() {
+            for address in queue {
+                image.fill_cell(*address, self.layout, warrior_colour(warrior.idx));
+            }
+        }
+
+        image
+    }
+}
+
+impl Logger for Animation {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        match event {
+            GameEvent::Executed(_)
+                if current_game_state
+                    .cycle_count()
+                    .is_multiple_of(self.interval) =>
+            {
+                if self.frames.len() < self.max_frames {
+                    let frame = self.draw(current_game_state);
+                    self.frames.push(frame);
+                } else {
+                    self.truncated = true;
+                }
+            }
+            GameEvent::GameOver(_) => {
+                let frame = self.draw(current_game_state);
+                if self.frames.len() >= self.max_frames {
+                    self.frames.pop();
+                    self.truncated = true;
+                }
+                self.frames.push(frame);
+            }
+            _ => {}
+        }
+    }
+
+    fn reset(&mut self) {
+        self.frames.clear();
+        self.truncated = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{CoreBuilder, Separation},
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn animates_a_round() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .core_size(100)
+            .read_distance(100)
+            .write_distance(100)
+            .cycles_before_tie(400)
+            .separation(Separation::Fixed(50))
+            .track_ownership(true)
+            .load_warriors(&[imp, dwarf])
+            .unwrap();
+        let layout = Layout::square(100, 1);
+        let mut animation = Animation::new(100, layout);
+
+        let mut core = builder.build().unwrap();
+        core.log_with(&mut animation).run();
+        drop(core);
+
+        // A frame every 100 cycles, and one for the end of the round.
+        assert_eq!(animation.frames().len(), 5);
+        let first = &animation.frames()[0];
+        assert_eq!((first.width(), first.height()), (10, 10));
+        let cell = |image: &Image, address: usize| {
+            let pixel = &image.pixels()[address * 3..address * 3 + 3];
+            [pixel[0], pixel[1], pixel[2]]
+        };
+        let [r, g, b] = warrior_colour(1);
+        assert_eq!(cell(first, 1), warrior_colour(0));
+        assert_eq!(cell(first, 51), [r / 3, g / 3, b / 3]);
+        assert_eq!(cell(first, 52), warrior_colour(1));
+        assert_eq!(cell(first, 60), [0, 0, 0]);
+
+        assert!(!animation.truncated());
+
+        let mut gif = Vec::new();
+        animation.write_gif(&mut gif, 10).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+
+        // With room for only two frames, the second is replaced by the end of the round.
+        let mut capped = Animation::new(100, layout);
+        capped.max_frames(2);
+        let mut core = builder.build().unwrap();
+        core.log_with(&mut capped).run();
+        drop(core);
+        assert!(capped.truncated());
+        assert_eq!(capped.frames().len(), 2);
+        assert_eq!(capped.frames()[0], animation.frames()[0]);
+        assert_eq!(capped.frames()[1], animation.frames()[4]);
+    }
+}
@@ -0,0 +1,205 @@
+use super::{warrior_colour, Image, Layout};
+use crate::{
+    core::Core,
+    logger::{AccessKind, GameEvent, Logger},
+};
+use std::str::FromStr;
+
+/// The kinds of activity a [`Heatmap`](struct.Heatmap.html) counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    Read,
+    /// Writes, including increments and decrements by addressing modes and `DJN`.
+    Write,
+    Execute,
+    /// Reads, writes and executions together.
+    All,
+}
+
+impl FromStr for Activity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "read" | "reads" => Ok(Activity::Read),
+            "write" | "writes" => Ok(Activity::Write),
+            "execute" | "executions" => Ok(Activity::Execute),
+            "all" => Ok(Activity::All),
+            _ => Err(format!(
+                "Unknown activity {}, expected read, write, execute or all",
+                s
+            )),
+        }
+    }
+}
+
+/// How many times a warrior read, wrote and executed a single cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CellActivity {
+    pub reads: u64,
+    pub writes: u64,
+    pub executions: u64,
+}
+
+impl CellActivity {
+    /// The count of the given kind of activity.
+    pub fn count(&self, activity: Activity) -> u64 {
+        match activity {
+            Activity::Read => self.reads,
+            Activity::Write => self.writes,
+            Activity::Execute => self.executions,
+            Activity::All => self.reads + self.writes + self.executions,
+        }
+    }
+}
+
+/// A `Logger` that counts how often each warrior reads, writes and executes each cell of the
+/// core, over one or more rounds, and draws the counts as an image.
+///
+/// The counts are kept by warrior index, so every round should be fought between the same
+/// warriors in a core of the same size.
+#[derive(Debug, Clone, Default)]
+pub struct Heatmap {
+    core_size: usize,
+    /// The activity of each warrior, by warrior index, in each cell.
+    warriors: Vec<Vec<CellActivity>>,
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The size of the core the rounds were fought in, or 0 if nothing has been logged.
+    pub fn core_size(&self) -> usize {
+        self.core_size
+    }
+
+    /// The activity of the warrior with index `warrior` in each cell, if it has done anything.
+    pub fn warrior(&self, warrior: usize) -> Option<&[CellActivity]> {
+        self.warriors.get(warrior).map(Vec::as_slice)
+    }
+
+    /// Draws the given kind of activity. Each warrior's activity is drawn in its colour from the
+    /// [`PALETTE`](constant.PALETTE.html), brighter where it is more active, on a logarithmic
+    /// scale. Cells used by more than one warrior get a mix of their colours.
+    pub fn render(&self, activity: Activity, layout: Layout) -> Image {
+        let mut image = Image::for_core(self.core_size, layout);
+        let max = self
+            .warriors
+            .iter()
+            .flatten()
+            .map(|cell| cell.count(activity))
+            .max()
+            .unwrap_or(0);
+        if max == 0 {
+            return image;
+        }
+        let scale = ((max + 1) as f64).ln();
+
+        for address in 0..self.core_size {
+            let mut colour = [0.0; 3];
+            for (idx, cells) in self.warriors.iter().enumerate() {
+                let count = cells[address].count(activity);
+                if count == 0 {
+                    continue;
+                }
+                let intensity = ((count + 1) as f64).ln() / scale;
+                for (channel, value) in colour.iter_mut().zip(warrior_colour(idx).iter()) {
+                    *channel += f64::from(*value) * intensity;
+                }
+            }
+            let colour = [
+                colour[0].min(255.0) as u8,
+                colour[1].min(255.0) as u8,
+                colour[2].min(255.0) as u8,
+            ];
+            image.fill_cell(address, layout, colour);
+        }
+
+        image
+    }
+
+    fn cell(&mut self, warrior: usize, address: usize) -> &mut CellActivity {
+        if self.warriors.len() <= warrior {
+            self.warriors
+                .resize(warrior + 1, vec![CellActivity::default(); self.core_size]);
+        }
+        &mut self.warriors[warrior][address]
+    }
+}
+
+impl Logger for Heatmap {
+    fn log(&mut self, current_game_state: &Core, event: &GameEvent) {
+        if let GameEvent::Executed(execution) = event {
+            if self.core_size == 0 {
+                self.core_size = current_game_state.instructions().len();
+            }
+
+            let warrior = execution.warrior.idx;
+            self.cell(warrior, execution.pc).executions += 1;
+            for access in &execution.accesses {
+                let cell = self.cell(warrior, access.address);
+                match access.kind {
+                    AccessKind::Read => cell.reads += 1,
+                    AccessKind::Write | AccessKind::Increment | AccessKind::Decrement => {
+                        cell.writes += 1
+                    }
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{CoreBuilder, Separation},
+        render::PALETTE,
+        warrior::Warrior,
+    };
+
+    #[test]
+    fn draws_a_heatmap() {
+        let dwarf = Warrior::parse(include_str!("../../warriors/dwarf.red"), 0).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .core_size(100)
+            .read_distance(100)
+            .write_distance(100)
+            .cycles_before_tie(300)
+            .separation(Separation::Fixed(10))
+            .load_warriors(&[dwarf])
+            .unwrap();
+        let mut heatmap = Heatmap::new();
+        for _ in 0..2 {
+            let mut core = builder.build().unwrap();
+            core.log_with(&mut heatmap).run();
+        }
+
+        // The dwarf's loop runs 100 times a round, bombing every fourth cell.
+        let cells = heatmap.warrior(0).unwrap();
+        assert_eq!(cells[2].executions, 200);
+        assert_eq!(cells[4].writes, 2 * 4);
+        assert_eq!(cells[5].count(Activity::All), 0);
+
+        let layout = Layout::square(100, 2);
+        let image = heatmap.render(Activity::Execute, layout);
+        assert_eq!((image.width(), image.height()), (20, 20));
+        assert_eq!(&image.pixels()[..3], &[0, 0, 0]);
+        assert_eq!(&image.pixels()[12..15], &PALETTE[0]);
+
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert_eq!(ppm.len(), "P6\n20 20\n255\n".len() + 20 * 20 * 3);
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
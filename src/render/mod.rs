@@ -0,0 +1,138 @@
+//! Drawing rounds as images, without the terminal visualiser.
+//!
+//! The core is laid out as a 2-D grid, filled row by row from address 0, with each cell drawn as
+//! a square of pixels.
+mod animation;
+mod heatmap;
+pub use animation::*;
+pub use heatmap::*;
+
+use crate::error::RenderError;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// The colours given to warriors, by index. Warriors past the end of the list reuse them.
+pub const PALETTE: [[u8; 3]; 8] = [
+    [230, 60, 60],
+    [60, 200, 80],
+    [70, 120, 240],
+    [240, 200, 40],
+    [200, 80, 220],
+    [40, 210, 210],
+    [250, 140, 40],
+    [170, 170, 170],
+];
+
+/// The colour of the warrior with index `warrior`.
+pub fn warrior_colour(warrior: usize) -> [u8; 3] {
+    PALETTE[warrior % PALETTE.len()]
+}
+
+/// The size of each cell of the core in an image, and how many cells are in each row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    /// The number of cells in each row.
+    pub columns: usize,
+    /// The width and height of each cell, in pixels.
+    pub scale: usize,
+}
+
+impl Layout {
+    /// A layout for a core of `core_size` cells which is roughly square, with each cell
+    /// `scale` pixels across.
+    pub fn square(core_size: usize, scale: usize) -> Self {
+        Self {
+            columns: ((core_size as f64).sqrt().ceil() as usize).max(1),
+            scale: scale.max(1),
+        }
+    }
+
+    /// The number of rows needed for a core of `core_size` cells.
+    pub fn rows(&self, core_size: usize) -> usize {
+        core_size.div_ceil(self.columns)
+    }
+}
+
+/// An RGB image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates a black image.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+        }
+    }
+
+    /// Creates a black image big enough for a core of `core_size` cells laid out by `layout`.
+    pub fn for_core(core_size: usize, layout: Layout) -> Self {
+        Self::new(
+            layout.columns * layout.scale,
+            layout.rows(core_size) * layout.scale,
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The image's pixels, as RGB triples row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Fills the square for the cell at `address` with `colour`.
+    pub fn fill_cell(&mut self, address: usize, layout: Layout, colour: [u8; 3]) {
+        let (x, y) = (
+            (address % layout.columns) * layout.scale,
+            (address / layout.columns) * layout.scale,
+        );
+        for row in y..(y + layout.scale).min(self.height) {
+            for column in x..(x + layout.scale).min(self.width) {
+                let i = (row * self.width + column) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&colour);
+            }
+        }
+    }
+
+    /// Writes the image as a binary PPM.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> Result<(), RenderError> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.pixels)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the image as a PNG.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), RenderError> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Saves the image to `path`, as a PPM if its extension is `.ppm` and as a PNG otherwise.
+    pub fn save(&self, path: &Path) -> Result<(), RenderError> {
+        let writer = BufWriter::new(File::create(path)?);
+        if path.extension().is_some_and(|ext| ext == "ppm") {
+            self.write_ppm(writer)
+        } else {
+            self.write_png(writer)
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Recording single rounds so that they can be shared and run again exactly.
+//!
+//! A [`Replay`](struct.Replay.html) holds everything needed to rebuild a core as it was at the
+//! start of a round: the core's settings, the source of each warrior and where each warrior
+//! started. Warriors are loaded where they were recorded, so replays don't depend on the random
+//! number generator placing them the same way from the round's seed, which is kept only as a
+//! record. The replay also records how the round ended, so that running it again can be checked
+//! against the original.
+use crate::{
+    core::{Core, CoreBuilder, InitialInstruction, MatchOutcome, Separation},
+    error::ReplayError,
+    score::RoundResult,
+    warrior::Warrior,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// The settings of the core a round was fought in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    core_size: usize,
+    cycles_before_tie: usize,
+    /// The instruction the core was filled with, as Redcode, or `None` if it was random.
+    initial_instruction: Option<String>,
+    instruction_limit: usize,
+    maximum_number_of_tasks: usize,
+    minimum_separation: usize,
+    read_distance: usize,
+    write_distance: usize,
+    separation: Separation,
+}
+
+/// A warrior in a recorded round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayWarrior {
+    /// The warrior's Redcode, with any EQU constants already substituted.
+    pub source: String,
+    /// The address of the warrior's first instruction to run.
+    pub position: usize,
+}
+
+/// How a recorded round ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    /// The positions of the warriors still alive at the end of the round: just the winner,
+    /// unless the round was a draw.
+    pub survivors: Vec<usize>,
+    /// The number of cycles the round lasted.
+    pub cycles: usize,
+}
+
+impl Display for ReplayOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.survivors.as_slice() {
+            [winner] => write!(f, "won by warrior {}", winner)?,
+            survivors => write!(f, "drawn between warriors {:?}", survivors)?,
+        }
+        write!(f, " after {} cycles", self.cycles)
+    }
+}
+
+/// A single round, recorded so it can be run again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    settings: Settings,
+    seed: u64,
+    warriors: Vec<ReplayWarrior>,
+    outcome: ReplayOutcome,
+}
+
+impl Replay {
+    /// Records the round that `core` has just finished with `outcome`.
+    pub fn new(core: &Core, outcome: &MatchOutcome) -> Result<Self, ReplayError> {
+        let builder = core.settings();
+        let positions = starting_positions(core);
+
+        Ok(Self {
+            settings: Settings {
+                core_size: builder.core_size,
+                cycles_before_tie: builder.cycles_before_tie,
+                initial_instruction: match builder.initial_instruction {
+                    InitialInstruction::Random => None,
+                    InitialInstruction::Fixed(ref instruction) => Some(instruction.to_string()),
+                },
+                instruction_limit: builder.instruction_limit,
+                maximum_number_of_tasks: builder.maximum_number_of_tasks,
+                minimum_separation: builder.minimum_separation,
+                read_distance: builder.read_distance,
+                write_distance: builder.write_distance,
+                separation: builder.separation.clone(),
+            },
+            seed: core.seed(),
+            warriors: builder
+                .warriors()
+                .iter()
+                .zip(positions)
+                .map(|(warrior, position)| ReplayWarrior {
+                    source: warrior.to_redcode(),
+                    position,
+                })
+                .collect(),
+            outcome: ReplayOutcome {
+                survivors: RoundResult::from_outcome(outcome, builder.warriors())
+                    .survivors()
+                    .to_vec(),
+                cycles: core.cycle_count(),
+            },
+        })
+    }
+
+    /// Loads a replay from `path`.
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Saves the replay to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// The seed the round's core was built with. Replaying doesn't use it, as the warriors are
+    /// loaded where they were recorded.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The warriors in the round, in the order they were loaded.
+    pub fn warriors(&self) -> &[ReplayWarrior] {
+        &self.warriors
+    }
+
+    /// How the round ended when it was recorded.
+    pub fn outcome(&self) -> &ReplayOutcome {
+        &self.outcome
+    }
+
+    /// Creates a `CoreBuilder` with the round's settings and warriors, which loads each warrior
+    /// where it was recorded as starting rather than where the seed would place it.
+    pub fn builder(&self) -> Result<CoreBuilder, ReplayError> {
+        let settings = &self.settings;
+        let initial_instruction = match settings.initial_instruction {
+            None => InitialInstruction::Random,
+            Some(ref source) => {
+                let warrior = Warrior::parse(&format!("        {}\n        END\n", source), 0)
+                    .map_err(|e| ReplayError::Warrior(source.clone(), e))?;
+                InitialInstruction::Fixed(warrior.instructions()[0].clone())
+            }
+        };
+        let warriors = self
+            .warriors
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                Warrior::parse(&w.source, i).map_err(|e| ReplayError::Warrior(w.source.clone(), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .core_size(settings.core_size)
+            .cycles_before_tie(settings.cycles_before_tie)
+            .initial_instruction(initial_instruction)
+            .instruction_limit(settings.instruction_limit)
+            .maximum_number_of_tasks(settings.maximum_number_of_tasks)
+            .minimum_separation(settings.minimum_separation)
+            .read_distance(settings.read_distance)
+            .write_distance(settings.write_distance)
+            .separation(Separation::Positions(
+                warriors
+                    .iter()
+                    .zip(&self.warriors)
+                    .map(|(warrior, w)| {
+                        let size = settings.core_size;
+                        (w.position + size - warrior.starts_at_line() % size) % size
+                    })
+                    .collect(),
+            ))
+            .load_warriors(&warriors)?;
+
+        Ok(builder)
+    }
+
+    /// Builds the round's core from `builder`, which should have come from
+    /// [`builder`](#method.builder), checking that the warriors start where they did when the
+    /// round was recorded.
+    pub fn build<'a, 'l>(&self, builder: &'a CoreBuilder) -> Result<Core<'a, 'l>, ReplayError> {
+        let core = builder.build_with_seed(self.seed)?;
+        let expected: Vec<usize> = self.warriors.iter().map(|w| w.position).collect();
+        let found = starting_positions(&core);
+        if found != expected {
+            return Err(ReplayError::Positions(expected, found));
+        }
+
+        Ok(core)
+    }
+
+    /// Checks that `core`, having been built by [`build`](#method.build) and run to the end,
+    /// finished the round the same way it did when it was recorded.
+    pub fn verify(&self, core: &Core, outcome: &MatchOutcome) -> Result<(), ReplayError> {
+        let found = ReplayOutcome {
+            survivors: RoundResult::from_outcome(outcome, core.settings().warriors())
+                .survivors()
+                .to_vec(),
+            cycles: core.cycle_count(),
+        };
+        if found != self.outcome {
+            return Err(ReplayError::Outcome(self.outcome.clone(), found));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the round again, checking that it goes exactly as it did when it was recorded.
+    pub fn run(&self) -> Result<ReplayOutcome, ReplayError> {
+        let builder = self.builder()?;
+        let mut core = self.build(&builder)?;
+        let outcome = core.run();
+        self.verify(&core, &outcome)?;
+
+        Ok(self.outcome.clone())
+    }
+}
+
+/// The address of each warrior's first instruction to run, whether or not it is still alive.
+fn starting_positions(core: &Core) -> Vec<usize> {
+    let size = core.instructions().len();
+    core.loaded_at()
+        .iter()
+        .zip(core.settings().warriors())
+        .map(|(start, warrior)| (start + warrior.starts_at_line()) % size)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replays_a_recorded_round() {
+        let warriors = vec![
+            Warrior::parse(include_str!("../warriors/stone.red"), 0).unwrap(),
+            Warrior::parse(include_str!("../warriors/imp.red"), 1).unwrap(),
+            Warrior::parse(include_str!("../warriors/dwarf.red"), 2).unwrap(),
+        ];
+        let mut builder = CoreBuilder::new();
+        builder.load_warriors(&warriors).unwrap();
+
+        let mut core = builder.build_with_seed(42).unwrap();
+        let outcome = core.run();
+        let replay = Replay::new(&core, &outcome).unwrap();
+
+        let json = serde_json::to_string(&replay).unwrap();
+        let loaded: Replay = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, replay);
+        assert_eq!(loaded.run().unwrap(), *replay.outcome());
+
+        let mut tampered = loaded.clone();
+        tampered.outcome.cycles += 1;
+        assert!(matches!(tampered.run(), Err(ReplayError::Outcome(..))));
+
+        // The warriors are placed where they were recorded, so the seed doesn't matter to
+        // where they start, and moving a warrior moves it in the replayed round.
+        let mut reseeded = loaded.clone();
+        reseeded.seed += 1;
+        assert_eq!(reseeded.run().unwrap(), *replay.outcome());
+        tampered.warriors[1].position += 1;
+        let builder = tampered.builder().unwrap();
+        let core = tampered.build(&builder).unwrap();
+        assert_eq!(
+            starting_positions(&core)[1],
+            replay.warriors()[1].position + 1
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Running many rounds of a match, either for a fixed number of rounds or until the results are
+//! known to a given precision.
+use crate::{
+    core::CoreBuilder,
+    error::CoreError,
+    score::{RoundResult, Sample, Scoring},
+};
+use indicatif::{ParallelProgressIterator, ProgressBar, ProgressIterator};
+use rayon::prelude::*;
+
+/// Runs rounds of a match configured by a [`CoreBuilder`](../core/struct.CoreBuilder.html),
+/// in parallel by default.
+#[derive(Debug, Clone, Default)]
+pub struct Runner {
+    single_threaded: bool,
+    show_progress: bool,
+}
+
+/// Settings for running rounds until the difference in score between every pair of warriors is
+/// known to a given precision.
+#[derive(Debug, Clone)]
+pub struct Adaptive {
+    /// The width, in percentage points, that the 95% confidence interval on the difference in
+    /// score between every pair of warriors must fall below.
+    pub target_width: f64,
+    /// The number of rounds to run between each check of the confidence intervals.
+    pub batch_size: usize,
+    /// The minimum number of rounds to run before the target width is accepted, so that a small
+    /// batch which happens to have no variance isn't taken as a precise result.
+    pub min_rounds: usize,
+    /// The maximum number of rounds to run, even if the target width hasn't been reached.
+    pub max_rounds: usize,
+}
+
+impl Default for Adaptive {
+    fn default() -> Self {
+        Self {
+            target_width: 5.0,
+            batch_size: 100,
+            min_rounds: 30,
+            max_rounds: 100_000,
+        }
+    }
+}
+
+/// The rounds run by [`Runner::run_adaptive`](struct.Runner.html#method.run_adaptive), along with
+/// the precision that was achieved.
+#[derive(Debug, Clone)]
+pub struct AdaptiveResults {
+    /// Every round that was run.
+    pub rounds: Vec<RoundResult>,
+    /// The widest 95% confidence interval on the difference in score between two warriors, in
+    /// percentage points.
+    pub width: f64,
+    /// Whether the target width was reached before the maximum number of rounds.
+    pub converged: bool,
+}
+
+impl Runner {
+    /// Creates a new `Runner`, which runs rounds in parallel without showing progress.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run rounds one after the other on the current thread.
+    pub fn single_threaded(&mut self, single_threaded: bool) -> &mut Self {
+        self.single_threaded = single_threaded;
+        self
+    }
+
+    /// Show a progress bar while running rounds.
+    pub fn show_progress(&mut self, show_progress: bool) -> &mut Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    fn progress_bar(&self, length: u64) -> ProgressBar {
+        if self.show_progress {
+            ProgressBar::new(length)
+        } else {
+            ProgressBar::hidden()
+        }
+    }
+
+    /// Runs `rounds` rounds of the match configured by `builder`, building a fresh core for each.
+    pub fn run(&self, builder: &CoreBuilder, rounds: usize) -> Result<Vec<RoundResult>, CoreError> {
+        let progress = self.progress_bar(rounds as u64);
+        let results = self.run_batch(builder, rounds, &progress);
+        progress.finish_and_clear();
+
+        results
+    }
+
+    /// Runs rounds of the match configured by `builder` in batches, until the 95% confidence
+    /// interval on the difference in score between every pair of warriors is narrower than
+    /// `settings.target_width`, or `settings.max_rounds` rounds have been run.
+    pub fn run_adaptive<S: Scoring + ?Sized>(
+        &self,
+        builder: &CoreBuilder,
+        settings: &Adaptive,
+        scoring: &S,
+    ) -> Result<AdaptiveResults, CoreError> {
+        let warriors = builder.warriors().len();
+        let max_points: Vec<f64> = (0..warriors)
+            .map(|w| scoring.points(&RoundResult::new(warriors, vec![w]), w))
+            .collect();
+        let mut differences = vec![Sample::new(); warriors * warriors];
+
+        let progress = self.progress_bar(settings.max_rounds as u64);
+        let mut rounds = Vec::new();
+        let mut width = f64::INFINITY;
+        let precise = |rounds: usize, width: f64| {
+            rounds >= settings.min_rounds.max(2) && width < settings.target_width
+        };
+
+        while rounds.len() < settings.max_rounds {
+            let batch_size = settings
+                .batch_size
+                .max(1)
+                .min(settings.max_rounds - rounds.len());
+            let batch = self.run_batch(builder, batch_size, &progress)?;
+
+            for round in &batch {
+                let percentages: Vec<f64> = (0..warriors)
+                    .map(|w| {
+                        if max_points[w] == 0.0 {
+                            0.0
+                        } else {
+                            scoring.points(round, w) / max_points[w] * 100.0
+                        }
+                    })
+                    .collect();
+
+                for a in 0..warriors {
+                    for b in (a + 1)..warriors {
+                        differences[a * warriors + b].push(percentages[a] - percentages[b]);
+                    }
+                }
+            }
+            rounds.extend(batch);
+
+            width = (0..warriors)
+                .flat_map(|a| ((a + 1)..warriors).map(move |b| (a, b)))
+                .map(|(a, b)| 2.0 * differences[a * warriors + b].margin_of_error())
+                .fold(0.0, f64::max);
+
+            if precise(rounds.len(), width) {
+                break;
+            }
+        }
+        progress.finish_and_clear();
+
+        Ok(AdaptiveResults {
+            converged: precise(rounds.len(), width),
+            rounds,
+            width,
+        })
+    }
+
+    /// Runs `rounds` rounds of each of the matches configured by `builders`, returning the
+    /// rounds of each match in the same order as `builders`. Rounds from different matches are
+    /// run in parallel with each other.
+    pub fn run_each(
+        &self,
+        builders: &[CoreBuilder],
+        rounds: usize,
+    ) -> Result<Vec<Vec<RoundResult>>, CoreError> {
+        if rounds == 0 {
+            return Ok(vec![Vec::new(); builders.len()]);
+        }
+
+        let progress = self.progress_bar((builders.len() * rounds) as u64);
+        let results = self.run_jobs(builders.len() * rounds, &progress, |job| {
+            run_round(&builders[job / rounds])
+        });
+        progress.finish_and_clear();
+
+        Ok(results?
+            .chunks(rounds)
+            .map(|rounds| rounds.to_vec())
+            .collect())
+    }
+
+    /// Runs one round of each of the matches configured by `builders` for every seed in
+    /// `seeds`, building each core with [`build_with_seed`][seeded]. Matches built from builders
+    /// with the same settings and the same seed start from the same positions, so their rounds
+    /// can be compared pairwise. The rounds of each match are returned in the order of `seeds`.
+    ///
+    /// [seeded]: ../core/struct.CoreBuilder.html#method.build_with_seed
+    pub fn run_each_seeded(
+        &self,
+        builders: &[CoreBuilder],
+        seeds: &[u64],
+    ) -> Result<Vec<Vec<RoundResult>>, CoreError> {
+        if seeds.is_empty() {
+            return Ok(vec![Vec::new(); builders.len()]);
+        }
+
+        let rounds = seeds.len();
+        let progress = self.progress_bar((builders.len() * rounds) as u64);
+        let results = self.run_jobs(builders.len() * rounds, &progress, |job| {
+            let builder = &builders[job / rounds];
+            let mut core = builder.build_with_seed(seeds[job % rounds])?;
+            let outcome = core.run();
+            Ok(RoundResult::from_outcome(&outcome, builder.warriors()))
+        });
+        progress.finish_and_clear();
+
+        Ok(results?
+            .chunks(rounds)
+            .map(|rounds| rounds.to_vec())
+            .collect())
+    }
+
+    fn run_batch(
+        &self,
+        builder: &CoreBuilder,
+        rounds: usize,
+        progress: &ProgressBar,
+    ) -> Result<Vec<RoundResult>, CoreError> {
+        self.run_jobs(rounds, progress, |_| run_round(builder))
+    }
+
+    fn run_jobs<F>(
+        &self,
+        jobs: usize,
+        progress: &ProgressBar,
+        job: F,
+    ) -> Result<Vec<RoundResult>, CoreError>
+    where
+        F: Fn(usize) -> Result<RoundResult, CoreError> + Send + Sync,
+    {
+        if self.single_threaded {
+            (0..jobs).progress_with(progress.clone()).map(job).collect()
+        } else {
+            (0..jobs)
+                .into_par_iter()
+                .progress_with(progress.clone())
+                .map(job)
+                .collect()
+        }
+    }
+}
+
+fn run_round(builder: &CoreBuilder) -> Result<RoundResult, CoreError> {
+    let mut core = builder.build()?;
+    let outcome = core.run();
+    Ok(RoundResult::from_outcome(&outcome, builder.warriors()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::Separation, score::Standard, warrior::Warrior};
+
+    #[test]
+    fn runs_fixed_number_of_rounds() {
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 0).unwrap();
+        let imp_gate = Warrior::parse(include_str!("../warriors/impgate.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .separation(Separation::Fixed(500))
+            .load_warriors(&[imp, imp_gate])
+            .unwrap();
+
+        let rounds = Runner::new().run(&builder, 8).unwrap();
+        assert_eq!(rounds, vec![RoundResult::new(2, vec![1]); 8]);
+
+        let rounds = Runner::new()
+            .single_threaded(true)
+            .run(&builder, 3)
+            .unwrap();
+        assert_eq!(rounds.len(), 3);
+    }
+
+    #[test]
+    fn adaptive_stops_once_precise() {
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 0).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder
+            .cycles_before_tie(1000)
+            .load_warriors(&[imp.clone(), imp])
+            .unwrap();
+
+        let settings = Adaptive {
+            target_width: 1.0,
+            batch_size: 10,
+            min_rounds: 30,
+            max_rounds: 1000,
+        };
+        let results = Runner::new()
+            .run_adaptive(&builder, &settings, &Standard::new())
+            .unwrap();
+
+        // Every batch has no variance, but the run goes on until the minimum number of rounds.
+        assert!(results.converged);
+        assert_eq!(results.rounds.len(), 30);
+        assert_eq!(results.width, 0.0);
+
+        let results = Runner::new()
+            .run_adaptive(
+                &builder,
+                &Adaptive {
+                    max_rounds: 20,
+                    ..settings
+                },
+                &Standard::new(),
+            )
+            .unwrap();
+        assert!(!results.converged);
+        assert_eq!(results.rounds.len(), 20);
+    }
+}
@@ -0,0 +1,287 @@
+//! Scoring systems, used to turn the results of many rounds into points for each warrior.
+//!
+//! A [`Scoring`](trait.Scoring.html) awards points to each warrior for a single
+//! [`RoundResult`](struct.RoundResult.html). Two common systems are provided: [`Standard`](struct.Standard.html)
+//! head-to-head scoring (3 points for a win, 1 for a tie), and pMARS-style [`Melee`](struct.Melee.html)
+//! scoring, in which each survivor is awarded (W*W-1)/S points. Any closure taking a round and
+//! a warrior's position can also be used as a scoring system.
+mod results;
+mod sample;
+pub use results::*;
+pub use sample::*;
+
+use crate::{core::MatchOutcome, warrior::Warrior};
+
+/// The result of a single round, in terms of the positions of the warriors that took part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundResult {
+    warriors: usize,
+    survivors: Vec<usize>,
+}
+
+impl RoundResult {
+    /// Creates a new `RoundResult` for a round fought between `warriors` warriors, of which
+    /// those at the positions in `survivors` were still alive at the end of the round.
+    pub fn new(warriors: usize, survivors: Vec<usize>) -> Self {
+        Self {
+            warriors,
+            survivors,
+        }
+    }
+
+    /// Creates a `RoundResult` from the outcome of a match.
+    ///
+    /// Warriors in the outcome are matched against `participants` by identity, so `participants`
+    /// should be the warriors that the core was built with (see
+    /// [`CoreBuilder::warriors`](../core/struct.CoreBuilder.html#method.warriors)). Warriors which
+    /// can't be found by identity are matched by equality instead.
+    pub fn from_outcome(outcome: &MatchOutcome, participants: &[Warrior]) -> Self {
+        let position = |warrior: &Warrior| {
+            participants
+                .iter()
+                .position(|p| std::ptr::eq(p, warrior))
+                .or_else(|| participants.iter().position(|p| p == warrior))
+        };
+
+        let survivors = match outcome {
+            MatchOutcome::Win(winner) => position(winner).into_iter().collect(),
+            MatchOutcome::Draw(remaining) => remaining.iter().filter_map(|w| position(w)).collect(),
+        };
+
+        Self::new(participants.len(), survivors)
+    }
+
+    /// The number of warriors that took part in the round.
+    pub fn warriors(&self) -> usize {
+        self.warriors
+    }
+
+    /// The positions of the warriors still alive at the end of the round.
+    pub fn survivors(&self) -> &[usize] {
+        &self.survivors
+    }
+
+    /// Whether the warrior at position `warrior` was alive at the end of the round.
+    pub fn survived(&self, warrior: usize) -> bool {
+        self.survivors.contains(&warrior)
+    }
+
+    /// Whether the warrior at position `warrior` was the only survivor of the round.
+    pub fn won(&self, warrior: usize) -> bool {
+        self.survivors == [warrior]
+    }
+
+    /// Whether the warrior at position `warrior` survived the round alongside others.
+    pub fn tied(&self, warrior: usize) -> bool {
+        self.survivors.len() > 1 && self.survived(warrior)
+    }
+}
+
+/// A system for awarding points to warriors based on the result of a round.
+pub trait Scoring: Send + Sync {
+    /// The number of points awarded to the warrior at position `warrior` for `round`.
+    fn points(&self, round: &RoundResult, warrior: usize) -> f64;
+}
+
+impl<F> Scoring for F
+where
+    F: Fn(&RoundResult, usize) -> f64 + Send + Sync,
+{
+    fn points(&self, round: &RoundResult, warrior: usize) -> f64 {
+        self(round, warrior)
+    }
+}
+
+/// Standard head-to-head scoring. By default a win is worth 3 points, a tie 1 point and a loss
+/// nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standard {
+    pub win: f64,
+    pub tie: f64,
+    pub loss: f64,
+}
+
+impl Default for Standard {
+    fn default() -> Self {
+        Self {
+            win: 3.0,
+            tie: 1.0,
+            loss: 0.0,
+        }
+    }
+}
+
+impl Standard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Scoring for Standard {
+    fn points(&self, round: &RoundResult, warrior: usize) -> f64 {
+        if round.won(warrior) {
+            self.win
+        } else if round.survived(warrior) {
+            self.tie
+        } else {
+            self.loss
+        }
+    }
+}
+
+/// pMARS melee scoring. Each surviving warrior is awarded (W*W-1)/S points, where W is the number
+/// of warriors in the round and S the number of survivors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Melee;
+
+impl Scoring for Melee {
+    fn points(&self, round: &RoundResult, warrior: usize) -> f64 {
+        if !round.survived(warrior) {
+            return 0.0;
+        }
+
+        let w = round.warriors() as f64;
+        let s = round.survivors().len() as f64;
+
+        (w * w - 1.0) / s
+    }
+}
+
+/// The total number of points scored by each warrior over a number of rounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreTable {
+    scores: Vec<f64>,
+    rounds: usize,
+}
+
+impl ScoreTable {
+    /// Creates an empty table for `warriors` warriors.
+    pub fn new(warriors: usize) -> Self {
+        Self {
+            scores: vec![0.0; warriors],
+            rounds: 0,
+        }
+    }
+
+    /// Creates a table by scoring every round in `rounds` with `scoring`.
+    pub fn from_rounds<'r, S, I>(scoring: &S, warriors: usize, rounds: I) -> Self
+    where
+        S: Scoring + ?Sized,
+        I: IntoIterator<Item = &'r RoundResult>,
+    {
+        let mut table = Self::new(warriors);
+        for round in rounds {
+            table.add_round(scoring, round);
+        }
+
+        table
+    }
+
+    /// Adds the points awarded by `scoring` for `round` to the table.
+    pub fn add_round<S: Scoring + ?Sized>(&mut self, scoring: &S, round: &RoundResult) {
+        for (warrior, score) in self.scores.iter_mut().enumerate() {
+            *score += scoring.points(round, warrior);
+        }
+        self.rounds += 1;
+    }
+
+    /// The total score of the warrior at position `warrior`.
+    pub fn score(&self, warrior: usize) -> f64 {
+        self.scores[warrior]
+    }
+
+    /// The total score of every warrior, by position.
+    pub fn scores(&self) -> &[f64] {
+        &self.scores
+    }
+
+    /// The number of rounds that have been scored.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// The positions of the warriors, ordered from highest to lowest score.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.scores.len()).collect();
+        ranking.sort_by(|&a, &b| self.scores[b].total_cmp(&self.scores[a]));
+        ranking
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::{CoreBuilder, Separation};
+
+    #[test]
+    fn standard_scoring() {
+        let scoring = Standard::new();
+        let win = RoundResult::new(2, vec![1]);
+        let tie = RoundResult::new(2, vec![0, 1]);
+
+        assert_eq!(scoring.points(&win, 0), 0.0);
+        assert_eq!(scoring.points(&win, 1), 3.0);
+        assert_eq!(scoring.points(&tie, 0), 1.0);
+        assert_eq!(scoring.points(&tie, 1), 1.0);
+    }
+
+    #[test]
+    fn melee_scoring() {
+        let round = RoundResult::new(4, vec![0, 2, 3]);
+
+        assert_eq!(Melee.points(&round, 0), 5.0);
+        assert_eq!(Melee.points(&round, 1), 0.0);
+        assert_eq!(Melee.points(&RoundResult::new(4, vec![2]), 2), 15.0);
+    }
+
+    #[test]
+    fn closure_scoring() {
+        let survival = |round: &RoundResult, warrior| {
+            if round.survived(warrior) {
+                1.0
+            } else {
+                -1.0
+            }
+        };
+        let rounds = vec![
+            RoundResult::new(3, vec![0]),
+            RoundResult::new(3, vec![0, 1]),
+            RoundResult::new(3, vec![1, 2]),
+        ];
+
+        let table = ScoreTable::from_rounds(&survival, 3, &rounds);
+        assert_eq!(table.scores(), &[1.0, 1.0, -1.0]);
+        assert_eq!(table.rounds(), 3);
+    }
+
+    #[test]
+    fn score_table_ranking() {
+        let rounds = vec![
+            RoundResult::new(3, vec![2]),
+            RoundResult::new(3, vec![0, 2]),
+            RoundResult::new(3, vec![1]),
+        ];
+
+        let table = ScoreTable::from_rounds(&Melee, 3, &rounds);
+        assert_eq!(table.scores(), &[4.0, 8.0, 12.0]);
+        assert_eq!(table.ranking(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn round_result_from_outcome() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let imp_gate = Warrior::parse(include_str!("../../warriors/impgate.red"), 0).unwrap();
+
+        let mut cb = CoreBuilder::new();
+        cb.load_warriors(&[imp.clone(), imp.clone(), imp_gate])
+            .unwrap()
+            .separation(Separation::Fixed(500));
+        let mut core = cb.build().unwrap();
+        let outcome = core.run();
+
+        let result = RoundResult::from_outcome(&outcome, cb.warriors());
+        assert_eq!(result.warriors(), 3);
+        assert_eq!(result.survivors(), &[2]);
+        assert!(result.won(2));
+    }
+}
@@ -0,0 +1,228 @@
+use super::{RoundResult, Sample, Scoring};
+use crate::warrior::Warrior;
+use std::fmt::{Display, Formatter};
+
+/// The aggregated results of a single warrior over a number of rounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarriorResults {
+    name: String,
+    wins: usize,
+    losses: usize,
+    ties: usize,
+    points: Sample,
+    max_score: f64,
+}
+
+impl WarriorResults {
+    fn new(warrior: &Warrior) -> Self {
+        Self {
+            name: warrior.metadata.display_name(),
+            wins: 0,
+            losses: 0,
+            ties: 0,
+            points: Sample::new(),
+            max_score: 0.0,
+        }
+    }
+
+    /// The warrior's name and author.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of rounds in which the warrior was the only survivor.
+    pub fn wins(&self) -> usize {
+        self.wins
+    }
+
+    /// The number of rounds in which the warrior was killed.
+    pub fn losses(&self) -> usize {
+        self.losses
+    }
+
+    /// The number of rounds in which the warrior survived alongside others.
+    pub fn ties(&self) -> usize {
+        self.ties
+    }
+
+    /// The number of rounds the warrior took part in.
+    pub fn rounds(&self) -> usize {
+        self.wins + self.losses + self.ties
+    }
+
+    /// The warrior's total score.
+    pub fn score(&self) -> f64 {
+        self.points.sum()
+    }
+
+    /// The warrior's score as a percentage of the score it would have had by winning every round.
+    pub fn percentage(&self) -> f64 {
+        if self.max_score == 0.0 {
+            return 0.0;
+        }
+
+        self.score() / self.max_score * 100.0
+    }
+
+    /// The standard error of [`percentage`](#method.percentage), estimated from the spread of the
+    /// warrior's scores in each round.
+    pub fn standard_error(&self) -> f64 {
+        if self.max_score == 0.0 {
+            return 0.0;
+        }
+
+        let max_per_round = self.max_score / self.rounds() as f64;
+        self.points.standard_error() / max_per_round * 100.0
+    }
+
+    fn add_round<S: Scoring + ?Sized>(&mut self, scoring: &S, round: &RoundResult, warrior: usize) {
+        if round.won(warrior) {
+            self.wins += 1;
+        } else if round.survived(warrior) {
+            self.ties += 1;
+        } else {
+            self.losses += 1;
+        }
+
+        self.points.push(scoring.points(round, warrior));
+        self.max_score +=
+            scoring.points(&RoundResult::new(round.warriors(), vec![warrior]), warrior);
+    }
+}
+
+/// The results of every warrior taking part in a number of rounds, by position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Results {
+    warriors: Vec<WarriorResults>,
+    rounds: usize,
+}
+
+impl Results {
+    /// Creates empty results for `participants`.
+    pub fn new(participants: &[Warrior]) -> Self {
+        Self {
+            warriors: participants.iter().map(WarriorResults::new).collect(),
+            rounds: 0,
+        }
+    }
+
+    /// Creates results for `participants` by scoring every round in `rounds` with `scoring`.
+    pub fn from_rounds<'r, S, I>(scoring: &S, participants: &[Warrior], rounds: I) -> Self
+    where
+        S: Scoring + ?Sized,
+        I: IntoIterator<Item = &'r RoundResult>,
+    {
+        let mut results = Self::new(participants);
+        for round in rounds {
+            results.add_round(scoring, round);
+        }
+
+        results
+    }
+
+    /// Adds a round, scored with `scoring`, to the results.
+    pub fn add_round<S: Scoring + ?Sized>(&mut self, scoring: &S, round: &RoundResult) {
+        for (i, warrior) in self.warriors.iter_mut().enumerate() {
+            warrior.add_round(scoring, round, i);
+        }
+        self.rounds += 1;
+    }
+
+    /// The results of the warrior at position `warrior`.
+    pub fn warrior(&self, warrior: usize) -> &WarriorResults {
+        &self.warriors[warrior]
+    }
+
+    /// The results of every warrior, by position.
+    pub fn warriors(&self) -> &[WarriorResults] {
+        &self.warriors
+    }
+
+    /// The number of rounds that have been added.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// The positions of the warriors, ordered from highest to lowest score.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.warriors.len()).collect();
+        ranking.sort_by(|&a, &b| {
+            self.warriors[b]
+                .score()
+                .total_cmp(&self.warriors[a].score())
+        });
+        ranking
+    }
+}
+
+impl Display for Results {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>3}  {:<40} {:>6} {:>6} {:>6} {:>10} {:>7} {:>6}",
+            "#", "Warrior", "Wins", "Losses", "Ties", "Score", "%", "±SE"
+        )?;
+        for (i, warrior) in self.warriors.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}  {:<40} {:>6} {:>6} {:>6} {:>10.1} {:>7.2} {:>6.2}",
+                i,
+                warrior.name,
+                warrior.wins,
+                warrior.losses,
+                warrior.ties,
+                warrior.score(),
+                warrior.percentage(),
+                warrior.standard_error()
+            )?;
+        }
+        write!(f, "{} rounds", self.rounds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::score::{Melee, Standard};
+
+    #[test]
+    fn copies_are_counted_separately() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let participants = vec![imp.clone(), imp];
+        let rounds = vec![
+            RoundResult::new(2, vec![0]),
+            RoundResult::new(2, vec![0, 1]),
+            RoundResult::new(2, vec![1]),
+            RoundResult::new(2, vec![0]),
+        ];
+
+        let results = Results::from_rounds(&Standard::new(), &participants, &rounds);
+
+        let first = results.warrior(0);
+        assert_eq!((first.wins(), first.losses(), first.ties()), (2, 1, 1));
+        assert_eq!(first.score(), 7.0);
+        assert!((first.percentage() - 7.0 / 12.0 * 100.0).abs() < 1e-9);
+
+        let second = results.warrior(1);
+        assert_eq!((second.wins(), second.losses(), second.ties()), (1, 2, 1));
+        assert_eq!(second.score(), 4.0);
+        assert_eq!(results.ranking(), vec![0, 1]);
+    }
+
+    #[test]
+    fn standard_error() {
+        let imp = Warrior::parse(include_str!("../../warriors/imp.red"), 0).unwrap();
+        let participants = vec![imp.clone(), imp.clone(), imp];
+
+        let always_ties = vec![RoundResult::new(3, vec![0, 1, 2]); 10];
+        let results = Results::from_rounds(&Melee, &participants, &always_ties);
+        assert_eq!(results.warrior(0).standard_error(), 0.0);
+        assert!((results.warrior(0).percentage() - 100.0 / 3.0).abs() < 1e-9);
+
+        let alternating: Vec<RoundResult> =
+            (0..10).map(|i| RoundResult::new(3, vec![i % 2])).collect();
+        let results = Results::from_rounds(&Melee, &participants, &alternating);
+        let se = results.warrior(0).standard_error();
+        assert!((se - (50.0 * 50.0 / 9.0f64).sqrt()).abs() < 1e-9);
+    }
+}
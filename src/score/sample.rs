@@ -0,0 +1,92 @@
+/// The z-value for a two-sided 95% confidence interval.
+pub const Z_95: f64 = 1.96;
+
+/// A running sample of values, used to estimate how precisely their mean is known.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sample {
+    len: usize,
+    sum: f64,
+    sum_squared: f64,
+}
+
+impl Sample {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the sample.
+    pub fn push(&mut self, value: f64) {
+        self.len += 1;
+        self.sum += value;
+        self.sum_squared += value * value;
+    }
+
+    /// The number of values in the sample.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The sum of the values in the sample.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// The mean of the values in the sample, or zero if it is empty.
+    pub fn mean(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        self.sum / self.len as f64
+    }
+
+    /// The unbiased sample variance, or zero if there are fewer than two values.
+    pub fn variance(&self) -> f64 {
+        if self.len < 2 {
+            return 0.0;
+        }
+
+        let n = self.len as f64;
+        let mean = self.mean();
+        ((self.sum_squared - n * mean * mean) / (n - 1.0)).max(0.0)
+    }
+
+    /// The standard error of the mean.
+    pub fn standard_error(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        (self.variance() / self.len as f64).sqrt()
+    }
+
+    /// The half-width of the 95% confidence interval around the mean.
+    pub fn margin_of_error(&self) -> f64 {
+        Z_95 * self.standard_error()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample_statistics() {
+        let mut sample = Sample::new();
+        assert_eq!(sample.mean(), 0.0);
+        assert_eq!(sample.standard_error(), 0.0);
+
+        for &value in &[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            sample.push(value);
+        }
+
+        assert_eq!(sample.len(), 8);
+        assert_eq!(sample.mean(), 5.0);
+        assert!((sample.variance() - 32.0 / 7.0).abs() < 1e-9);
+        assert!((sample.standard_error() - (4.0f64 / 7.0).sqrt()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,371 @@
+//! Finding the starting positions a warrior wins or loses from.
+//!
+//! A warrior fights an opponent with the opponent loaded at every offset in a range, using
+//! [`Separation::Fixed`](../core/enum.Separation.html), and the results at each offset are
+//! grouped into bands of offsets with the same result. Rounds from a fixed offset only differ if
+//! the core starts out random, so one round per offset is usually enough.
+use crate::{
+    core::{CoreBuilder, Separation},
+    error::CoreError,
+    runner::Runner,
+    warrior::Warrior,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+/// Runs a pairing across a range of offsets.
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    from: Option<usize>,
+    to: Option<usize>,
+    step: usize,
+    rounds: usize,
+    runner: Runner,
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            step: 1,
+            rounds: 1,
+            runner: Runner::new(),
+        }
+    }
+}
+
+impl Sweep {
+    /// Creates a new sweep, which fights one round at every offset the core's minimum
+    /// separation allows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the first offset to try, as the distance from the warrior's first instruction to
+    /// the opponent's. By default this is the closest the core's minimum separation allows.
+    pub fn from(&mut self, offset: usize) -> &mut Self {
+        self.from = Some(offset);
+        self
+    }
+
+    /// Sets the last offset to try. By default this is the furthest the core's minimum
+    /// separation allows.
+    pub fn to(&mut self, offset: usize) -> &mut Self {
+        self.to = Some(offset);
+        self
+    }
+
+    /// Sets the distance between the offsets tried.
+    pub fn step(&mut self, step: usize) -> &mut Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Sets the number of rounds fought at each offset, which is at least 1, as an offset with
+    /// no rounds has no outcome.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds.max(1);
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Runs `warrior` against `opponent` at each offset, using the rest of the settings in
+    /// `builder`. Any warriors already loaded into `builder` are ignored.
+    pub fn run(
+        &self,
+        builder: &CoreBuilder,
+        warrior: &Warrior,
+        opponent: &Warrior,
+    ) -> Result<SensitivityReport, CoreError> {
+        let from = self
+            .from
+            .unwrap_or(warrior.len() + builder.minimum_separation);
+        let to = self.to.unwrap_or_else(|| {
+            builder
+                .core_size
+                .saturating_sub(opponent.len() + builder.minimum_separation)
+        });
+        let offsets: Vec<usize> = (from..=to)
+            .step_by(self.step)
+            .filter(|offset| *offset >= warrior.len())
+            .collect();
+
+        let builders = offsets
+            .iter()
+            .map(|offset| {
+                let mut builder = builder.clone();
+                builder
+                    .separation(Separation::Fixed(offset - warrior.len()))
+                    .load_warriors(&[warrior.clone(), opponent.clone()])?;
+                Ok(builder)
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+
+        let rounds = self.runner.run_each(&builders, self.rounds)?;
+
+        Ok(SensitivityReport {
+            warrior: warrior.metadata.display_name(),
+            opponent: opponent.metadata.display_name(),
+            rounds: self.rounds,
+            offsets: offsets
+                .into_iter()
+                .zip(rounds)
+                .map(|(offset, rounds)| OffsetResult {
+                    offset,
+                    wins: rounds.iter().filter(|r| r.won(0)).count(),
+                    losses: rounds.iter().filter(|r| !r.survived(0)).count(),
+                    ties: rounds.iter().filter(|r| r.tied(0)).count(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// The result of the rounds at an offset, for the warrior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Win,
+    Loss,
+    Tie,
+    /// The rounds didn't all end the same way.
+    Mixed,
+}
+
+impl Outcome {
+    fn symbol(self) -> char {
+        match self {
+            Outcome::Win => 'W',
+            Outcome::Loss => 'L',
+            Outcome::Tie => 'T',
+            Outcome::Mixed => '?',
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Outcome::Win => "win",
+                Outcome::Loss => "loss",
+                Outcome::Tie => "tie",
+                Outcome::Mixed => "mixed",
+            }
+        )
+    }
+}
+
+/// The warrior's results at a single offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffsetResult {
+    /// The distance from the warrior's first instruction to the opponent's.
+    pub offset: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+}
+
+impl OffsetResult {
+    pub fn outcome(&self) -> Outcome {
+        match (self.wins, self.losses, self.ties) {
+            (_, 0, 0) => Outcome::Win,
+            (0, _, 0) => Outcome::Loss,
+            (0, 0, _) => Outcome::Tie,
+            _ => Outcome::Mixed,
+        }
+    }
+}
+
+/// A run of consecutive offsets with the same outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Band {
+    /// The first offset in the band.
+    pub start: usize,
+    /// The last offset in the band.
+    pub end: usize,
+    pub outcome: Outcome,
+    /// The number of offsets tried in the band.
+    pub offsets: usize,
+}
+
+/// The results of a sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensitivityReport {
+    /// The warrior's name and author.
+    pub warrior: String,
+    /// The opponent's name and author.
+    pub opponent: String,
+    /// The number of rounds fought at each offset.
+    pub rounds: usize,
+    /// The results at each offset, in order.
+    pub offsets: Vec<OffsetResult>,
+}
+
+impl SensitivityReport {
+    /// The offsets grouped into bands with the same outcome, in order.
+    pub fn bands(&self) -> Vec<Band> {
+        let mut bands: Vec<Band> = Vec::new();
+        for result in &self.offsets {
+            match bands.last_mut() {
+                Some(band) if band.outcome == result.outcome() => {
+                    band.end = result.offset;
+                    band.offsets += 1;
+                }
+                _ => bands.push(Band {
+                    start: result.offset,
+                    end: result.offset,
+                    outcome: result.outcome(),
+                    offsets: 1,
+                }),
+            }
+        }
+
+        bands
+    }
+
+    /// The offsets where the outcome differs from the offset tried before it.
+    pub fn flips(&self) -> Vec<usize> {
+        self.bands().iter().skip(1).map(|band| band.start).collect()
+    }
+
+    /// Writes the results to `writer` as CSV, with a row for each offset.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "offset,wins,losses,ties,outcome")?;
+        for result in &self.offsets {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                result.offset,
+                result.wins,
+                result.losses,
+                result.ties,
+                result.outcome()
+            )?;
+        }
+
+        writer.flush()
+    }
+}
+
+impl Display for SensitivityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const WIDTH: usize = 64;
+
+        writeln!(
+            f,
+            "{} against {}, {} round{} at each of {} offsets",
+            self.warrior,
+            self.opponent,
+            self.rounds,
+            if self.rounds == 1 { "" } else { "s" },
+            self.offsets.len()
+        )?;
+        if self.offsets.is_empty() {
+            return Ok(());
+        }
+        let count = |outcome| {
+            self.offsets
+                .iter()
+                .filter(|r| r.outcome() == outcome)
+                .count() as f64
+                / self.offsets.len() as f64
+                * 100.0
+        };
+        writeln!(
+            f,
+            "Wins {:.1}%, losses {:.1}%, ties {:.1}%, mixed {:.1}%",
+            count(Outcome::Win),
+            count(Outcome::Loss),
+            count(Outcome::Tie),
+            count(Outcome::Mixed)
+        )?;
+
+        writeln!(f)?;
+        writeln!(f, "W win, L loss, T tie, ? mixed, ^ result flips")?;
+        let flips = self.flips();
+        for line in self.offsets.chunks(WIDTH) {
+            let symbols: String = line.iter().map(|r| r.outcome().symbol()).collect();
+            writeln!(f, "{:>7} {}", line[0].offset, symbols)?;
+            if line.iter().any(|r| flips.contains(&r.offset)) {
+                let marks: String = line
+                    .iter()
+                    .map(|r| if flips.contains(&r.offset) { '^' } else { ' ' })
+                    .collect();
+                writeln!(f, "{:>7} {}", "", marks.trim_end())?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{:>15} {:<6} {:>8}", "Offsets", "Result", "Count")?;
+        for band in self.bands() {
+            writeln!(
+                f,
+                "{:>15} {:<6} {:>8}",
+                format!("{}-{}", band.start, band.end),
+                band.outcome.to_string(),
+                band.offsets
+            )?;
+        }
+        write!(f, "Result flips at {} offsets", flips.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sweeps_offsets() {
+        let stone = Warrior::parse(include_str!("../warriors/stone.red"), 0).unwrap();
+        let wait = Warrior::parse(include_str!("../warriors/wait.red"), 1).unwrap();
+
+        let mut builder = CoreBuilder::new();
+        builder.cycles_before_tie(2000);
+        let report = Sweep::new()
+            .from(100)
+            .to(140)
+            .runner(Runner::new().single_threaded(true).clone())
+            .run(&builder, &stone, &wait)
+            .unwrap();
+
+        // The stone bombs every fourth cell, so only hits the waiting warrior at one offset in
+        // four.
+        assert_eq!(report.offsets.len(), 41);
+        for result in &report.offsets {
+            let expected = if result.offset % 4 == 3 {
+                Outcome::Win
+            } else {
+                Outcome::Tie
+            };
+            assert_eq!(result.outcome(), expected, "at offset {}", result.offset);
+        }
+        let bands = report.bands();
+        assert_eq!(bands.len(), 21);
+        assert_eq!((bands[0].start, bands[0].end), (100, 102));
+        assert_eq!(report.flips()[..2], [103, 104]);
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 42);
+
+        // Asking for no rounds still fights one, rather than reporting a win at every offset.
+        let report = Sweep::new()
+            .from(100)
+            .to(101)
+            .rounds(0)
+            .run(&builder, &stone, &wait)
+            .unwrap();
+        assert_eq!(report.rounds, 1);
+        assert_eq!(report.offsets[0].outcome(), Outcome::Tie);
+    }
+}
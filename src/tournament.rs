@@ -0,0 +1,300 @@
+//! Round-robin tournaments, in which every warrior fights every other warrior.
+use crate::{
+    core::CoreBuilder, error::CoreError, runner::Runner, score::Scoring, warrior::Warrior,
+};
+use std::fmt::{Display, Formatter};
+
+/// A round-robin tournament between a list of warriors.
+///
+/// Every pairing of two warriors is fought for a number of rounds. By default each pairing is
+/// fought once, with the warrior that comes first in the list loaded first, and warriors don't
+/// fight themselves.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    rounds: usize,
+    self_play: bool,
+    both_orders: bool,
+    runner: Runner,
+}
+
+impl Default for Tournament {
+    fn default() -> Self {
+        Self {
+            rounds: 100,
+            self_play: false,
+            both_orders: false,
+            runner: Runner::new(),
+        }
+    }
+}
+
+impl Tournament {
+    /// Creates a new tournament, in which each pairing is fought for 100 rounds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of rounds each pairing is fought for.
+    pub fn rounds(&mut self, rounds: usize) -> &mut Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Whether each warrior should also fight a copy of itself.
+    pub fn self_play(&mut self, self_play: bool) -> &mut Self {
+        self.self_play = self_play;
+        self
+    }
+
+    /// Whether each pairing should be fought with each warrior loaded first in turn.
+    pub fn both_orders(&mut self, both_orders: bool) -> &mut Self {
+        self.both_orders = both_orders;
+        self
+    }
+
+    /// Sets the `Runner` used to run the rounds of each pairing.
+    pub fn runner(&mut self, runner: Runner) -> &mut Self {
+        self.runner = runner;
+        self
+    }
+
+    /// The pairings fought in the tournament, as pairs of positions in the list of warriors.
+    pub fn pairings(&self, warriors: usize) -> Vec<(usize, usize)> {
+        let mut pairings = Vec::new();
+        for a in 0..warriors {
+            if self.self_play {
+                pairings.push((a, a));
+            }
+            for b in (a + 1)..warriors {
+                pairings.push((a, b));
+                if self.both_orders {
+                    pairings.push((b, a));
+                }
+            }
+        }
+
+        pairings
+    }
+
+    /// Runs the tournament between `warriors`, using the settings in `builder` for every round.
+    /// Any warriors already loaded into `builder` are ignored.
+    pub fn run<S: Scoring + ?Sized>(
+        &self,
+        builder: &CoreBuilder,
+        warriors: &[Warrior],
+        scoring: &S,
+    ) -> Result<TournamentResults, CoreError> {
+        self.run_pairings(builder, warriors, &self.pairings(warriors.len()), scoring)
+    }
+
+    /// Runs only the given pairings between `warriors`, rather than every pairing. Each pairing
+    /// is a pair of positions in `warriors`, with the first warrior loaded first.
+    pub fn run_pairings<S: Scoring + ?Sized>(
+        &self,
+        builder: &CoreBuilder,
+        warriors: &[Warrior],
+        pairings: &[(usize, usize)],
+        scoring: &S,
+    ) -> Result<TournamentResults, CoreError> {
+        let builders = pairings
+            .iter()
+            .map(|&(a, b)| {
+                let mut builder = builder.clone();
+                builder.load_warriors(&[warriors[a].clone(), warriors[b].clone()])?;
+                Ok(builder)
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+
+        let rounds = self.runner.run_each(&builders, self.rounds)?;
+
+        let mut results =
+            TournamentResults::new(warriors.iter().map(|w| w.metadata.display_name()).collect());
+        for (&(a, b), rounds) in pairings.iter().zip(rounds) {
+            for round in &rounds {
+                results.add_points(a, b, scoring.points(round, 0), scoring.points(round, 1), 1);
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+/// The results of a [`Tournament`](struct.Tournament.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResults {
+    names: Vec<String>,
+    points: Vec<f64>,
+    rounds: Vec<usize>,
+}
+
+impl TournamentResults {
+    pub(crate) fn new(names: Vec<String>) -> Self {
+        let n = names.len();
+        Self {
+            names,
+            points: vec![0.0; n * n],
+            rounds: vec![0; n * n],
+        }
+    }
+
+    /// Records `rounds` rounds between warriors `a` and `b`, in which they scored `a_points`
+    /// and `b_points` respectively.
+    pub(crate) fn add_points(
+        &mut self,
+        a: usize,
+        b: usize,
+        a_points: f64,
+        b_points: f64,
+        rounds: usize,
+    ) {
+        let n = self.names.len();
+        self.points[a * n + b] += a_points;
+        self.rounds[a * n + b] += rounds;
+        self.points[b * n + a] += b_points;
+        self.rounds[b * n + a] += rounds;
+    }
+
+    /// The number of warriors in the tournament.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The name and author of the warrior at position `warrior`.
+    pub fn name(&self, warrior: usize) -> &str {
+        &self.names[warrior]
+    }
+
+    /// The total number of points scored by warrior `a` in its rounds against warrior `b`.
+    pub fn points(&self, a: usize, b: usize) -> f64 {
+        self.points[a * self.len() + b]
+    }
+
+    /// The number of rounds fought by warrior `a` against warrior `b`.
+    pub fn rounds(&self, a: usize, b: usize) -> usize {
+        self.rounds[a * self.len() + b]
+    }
+
+    /// The score of warrior `a` against warrior `b`: the number of points it scored per 100
+    /// rounds, or `None` if the two didn't fight.
+    pub fn score(&self, a: usize, b: usize) -> Option<f64> {
+        match self.rounds(a, b) {
+            0 => None,
+            rounds => Some(self.points(a, b) / rounds as f64 * 100.0),
+        }
+    }
+
+    /// The total score of `warrior`: its average score against every warrior it fought.
+    pub fn total(&self, warrior: usize) -> f64 {
+        let scores: Vec<f64> = (0..self.len())
+            .filter_map(|opponent| self.score(warrior, opponent))
+            .collect();
+
+        if scores.is_empty() {
+            0.0
+        } else {
+            scores.iter().sum::<f64>() / scores.len() as f64
+        }
+    }
+
+    /// The positions of the warriors, ordered from highest to lowest total score.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.len()).collect();
+        ranking.sort_by(|&a, &b| self.total(b).total_cmp(&self.total(a)));
+        ranking
+    }
+}
+
+impl Display for TournamentResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:>3}  {:<40} {:>8}",
+            "Rank", "#", "Warrior", "Score"
+        )?;
+        for (rank, warrior) in self.ranking().into_iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:>3}  {:<40} {:>8.1}",
+                rank + 1,
+                warrior,
+                self.names[warrior],
+                self.total(warrior)
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(f, "{:>3} ", "")?;
+        for b in 0..self.len() {
+            write!(f, " {:>5}", b)?;
+        }
+        for a in 0..self.len() {
+            writeln!(f)?;
+            write!(f, "{:>3} ", a)?;
+            for b in 0..self.len() {
+                match self.score(a, b) {
+                    Some(score) => write!(f, " {:>5.0}", score)?,
+                    None => write!(f, " {:>5}", "-")?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{core::Separation, score::Standard};
+
+    #[test]
+    fn pairings() {
+        let mut tournament = Tournament::new();
+        assert_eq!(tournament.pairings(3), vec![(0, 1), (0, 2), (1, 2)]);
+
+        tournament.self_play(true).both_orders(true);
+        assert_eq!(
+            tournament.pairings(3),
+            vec![
+                (0, 0),
+                (0, 1),
+                (1, 0),
+                (0, 2),
+                (2, 0),
+                (1, 1),
+                (1, 2),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn imp_gate_beats_imps() {
+        let imp = Warrior::parse(include_str!("../warriors/imp.red"), 0).unwrap();
+        let imp_gate = Warrior::parse(include_str!("../warriors/impgate.red"), 0).unwrap();
+        let warriors = vec![imp.clone(), imp_gate, imp];
+
+        let mut builder = CoreBuilder::new();
+        builder.separation(Separation::Fixed(500));
+
+        let results = Tournament::new()
+            .rounds(4)
+            .both_orders(true)
+            .run(&builder, &warriors, &Standard::new())
+            .unwrap();
+
+        assert_eq!(results.rounds(0, 1), 8);
+        assert_eq!(results.rounds(1, 1), 0);
+        assert_eq!(results.score(1, 0), Some(300.0));
+        assert_eq!(results.score(0, 1), Some(0.0));
+        assert_eq!(results.score(0, 2), Some(100.0));
+        assert_eq!(results.score(1, 1), None);
+        assert_eq!(results.total(1), 300.0);
+        assert_eq!(results.ranking()[0], 1);
+    }
+}
@@ -10,13 +10,10 @@ type Senders = Vec<Sender<ControllerMessage>>;
 
 pub fn setup_controller(senders: Senders) -> crossterm::Result<()> {
     loop {
-        match read()? {
-            Event::Key(event) => {
-                if match_key_event(event, &senders) {
-                    break;
-                }
+        if let Event::Key(event) = read()? {
+            if match_key_event(event, &senders) {
+                break;
             }
-            _ => {}
         };
     }
 
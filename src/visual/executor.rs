@@ -1,10 +1,17 @@
 use super::controller::ControllerMessage;
 use crossbeam::channel::{Receiver, Sender};
-use mars::core::{Core, ExecutionOutcome};
+use mars::core::{Core, ExecutionOutcome, Ownership};
+
+/// The outcome of running a single instruction, along with the new owner of every cell it wrote
+/// to.
+pub struct Step {
+    pub outcome: ExecutionOutcome,
+    pub written: Vec<(usize, Option<Ownership>)>,
+}
 
 pub fn setup_executor(
     mut core: Core,
-    tx: Sender<ExecutionOutcome>,
+    tx: Sender<Step>,
     controller_rx: Receiver<ControllerMessage>,
 ) {
     loop {
@@ -18,10 +25,15 @@ pub fn setup_executor(
         if let ExecutionOutcome::GameOver = outcome {
             done = true;
         }
+        let written = core
+            .last_written()
+            .iter()
+            .map(|&address| (address, core.owner(address)))
+            .collect();
 
         // Transmit the solution (blocking if the queue is full).
         // If it's an error or we're done, break.
-        if tx.send(outcome).is_err() || done {
+        if tx.send(Step { outcome, written }).is_err() || done {
             break;
         };
     }
@@ -1,9 +1,8 @@
 use crossbeam::channel;
 use crossbeam::thread;
 use mars::core::Core;
-use mars::warrior::Warrior;
 use rand::Rng;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::time::Duration;
 use tui::style::Color;
 
@@ -12,8 +11,6 @@ mod executor;
 mod grid;
 mod visualiser;
 
-type TaskQueue<'a> = (Warrior, VecDeque<usize>);
-
 type ColorMap = HashMap<usize, (u8, u8, u8)>;
 
 #[derive(Clone, Copy)]
@@ -29,12 +26,11 @@ pub fn run_with_visualiser(core: Core) -> anyhow::Result<()> {
     let (executor_canceller_tx, executor_canceller_rx) = channel::unbounded();
     let (visualiser_canceller_tx, visualiser_canceller_rx) = channel::unbounded();
 
-    let core_size = core.instructions().len();
-    let task_queues: Vec<TaskQueue> = core
-        .task_queues()
-        .to_owned()
+    let owners: Vec<Option<usize>> = core
+        .owners()
+        .ok_or_else(|| anyhow::anyhow!("The visualiser needs the core to track ownership"))?
         .iter()
-        .map(|(warrior, queue)| ((*warrior).clone(), queue.to_owned()))
+        .map(|owner| owner.map(|o| o.warrior))
         .collect();
 
     let colours = core
@@ -59,8 +55,7 @@ pub fn run_with_visualiser(core: Core) -> anyhow::Result<()> {
             rx,
             visualiser_canceller_rx,
             Duration::from_millis(1),
-            core_size,
-            &task_queues,
+            &owners,
             colours,
         )
         .expect("Couldn't unwrap visualiser result");
@@ -1,8 +1,7 @@
 use super::grid::PlayGrid;
-use super::TaskQueue;
-use super::{controller::ControllerMessage, ColorMap, VisualiserPixel};
+use super::{controller::ControllerMessage, executor::Step, ColorMap, VisualiserPixel};
 use crossbeam::channel::Receiver;
-use mars::core::ExecutionOutcome;
+use mars::core::{CoreChange, ExecutionOutcome};
 use std::io::Stdout;
 use std::time::Duration;
 use tui::backend::{Backend, CrosstermBackend};
@@ -21,26 +20,14 @@ fn get_warrior_color(colours: &ColorMap, idx: usize) -> Color {
         .unwrap_or(Color::White)
 }
 
-fn generate_initial_grid(
-    core_size: usize,
-    task_queues: &[TaskQueue],
-    colours: &ColorMap,
-) -> Vec<VisualiserPixel> {
-    let mut visualised_core = vec![VisualiserPixel::Uninitialised; core_size];
-
-    for (warrior, queues) in task_queues {
-        let color = get_warrior_color(&colours, warrior.idx);
-
-        let length = warrior.len();
-
-        for &queue in queues {
-            for i in queue..(queue + length) {
-                visualised_core[i] = VisualiserPixel::Initialised(color);
-            }
-        }
-    }
-
-    visualised_core
+fn generate_initial_grid(owners: &[Option<usize>], colours: &ColorMap) -> Vec<VisualiserPixel> {
+    owners
+        .iter()
+        .map(|owner| match owner {
+            Some(idx) => VisualiserPixel::Initialised(get_warrior_color(colours, *idx)),
+            None => VisualiserPixel::Uninitialised,
+        })
+        .collect()
 }
 
 struct VisualiserState<'a> {
@@ -50,11 +37,10 @@ struct VisualiserState<'a> {
 }
 
 pub fn setup_visualiser(
-    rx: Receiver<ExecutionOutcome>,
+    rx: Receiver<Step>,
     controller_rx: Receiver<ControllerMessage>,
     step_delay: Duration,
-    core_size: usize,
-    task_queues: &[TaskQueue],
+    owners: &[Option<usize>],
     colours: ColorMap,
 ) -> anyhow::Result<()> {
     let mut state = VisualiserState {
@@ -65,7 +51,7 @@ pub fn setup_visualiser(
 
     let mut terminal = build_terminal()?;
 
-    let mut visualised_core = generate_initial_grid(core_size, task_queues, &colours);
+    let mut visualised_core = generate_initial_grid(owners, &colours);
 
     loop {
         match controller_rx.try_recv() {
@@ -101,21 +87,18 @@ pub fn setup_visualiser(
         })?;
 
         if !state.paused {
-            let event = rx.recv()?;
-            match event {
-                ExecutionOutcome::Continue(change) => match change {
-                    mars::core::CoreChange::WarriorPlayed {
-                        warrior_idx,
-                        task,
-                        destination_ptr,
-                        ..
-                    } => {
-                        visualised_core[task] = VisualiserPixel::Executing;
-                        visualised_core[destination_ptr] =
-                            VisualiserPixel::Touched(get_warrior_color(&colours, warrior_idx));
-                    }
-                    mars::core::CoreChange::WarriorKilled(_) => {}
-                },
+            let step = rx.recv()?;
+            for (address, owner) in step.written {
+                if let Some(owner) = owner {
+                    visualised_core[address] =
+                        VisualiserPixel::Touched(get_warrior_color(&colours, owner.warrior));
+                }
+            }
+            match step.outcome {
+                ExecutionOutcome::Continue(CoreChange::WarriorPlayed { task, .. }) => {
+                    visualised_core[task] = VisualiserPixel::Executing;
+                }
+                ExecutionOutcome::Continue(CoreChange::WarriorKilled(_)) => {}
                 ExecutionOutcome::GameOver => break,
             }
         }
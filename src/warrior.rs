@@ -3,7 +3,9 @@ use crate::parser::instruction::{
     Address, AddressMode, Modifier, Opcode, Operation, RawInstruction,
 };
 use crate::parser::line::Line;
-use crate::parser::{metadata::MetadataValue, numeric_expr::NumericExpr, replace_definitions};
+use crate::parser::{
+    metadata::MetadataValue, numeric_expr::NumericExpr, override_definitions, replace_definitions,
+};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
@@ -124,6 +126,30 @@ impl Metadata {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// The warrior's name and author, for showing in reports, e.g. `Imp by A. K. Dewdney`.
+    pub fn display_name(&self) -> String {
+        format!(
+            "{} by {}",
+            self.name().unwrap_or("Unnamed warrior"),
+            self.author().unwrap_or("Unnamed author")
+        )
+    }
+
+    /// Sets the warrior's name.
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// Sets the name of the warrior's author.
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    /// Sets the description of the warrior's strategy.
+    pub fn set_strategy(&mut self, strategy: Option<String>) {
+        self.strategy = strategy;
+    }
 }
 
 macro_rules! insert_once {
@@ -177,7 +203,7 @@ impl Metadata {
             MetadataValue::Strategy(strategy) => {
                 if let Some(ref mut strat) = self.strategy {
                     strat.push('\n');
-                    strat.push_str(&strategy);
+                    strat.push_str(strategy);
                 };
 
                 self.strategy = Some(String::from(strategy));
@@ -190,6 +216,8 @@ impl Metadata {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Warrior {
     pub metadata: Metadata,
+    /// The warrior's position among the warriors of a core, which
+    /// [`CoreBuilder::load_warriors`](../core/struct.CoreBuilder.html#method.load_warriors) sets.
     pub idx: usize,
     pub(crate) instructions: Vec<Instruction>,
     pub(crate) starts_at_line: usize,
@@ -209,11 +237,54 @@ impl Display for Warrior {
 
 impl Warrior {
     pub fn parse(input: &str, idx: usize) -> Result<Warrior, Error> {
-        let input = replace_definitions(input).map_err(Error::Parse)?;
+        Self::parse_with_overrides(input, idx, &HashMap::new(), false)
+    }
+
+    /// Parses a warrior like [`parse`](#method.parse), but with the EQU definitions named in
+    /// `overrides` replaced by the given values. Naming a constant the warrior doesn't define is
+    /// an error, unless `force` is set, in which case the definition is added.
+    pub fn parse_with_overrides(
+        input: &str,
+        idx: usize,
+        overrides: &HashMap<String, String>,
+        force: bool,
+    ) -> Result<Warrior, Error> {
+        let input = override_definitions(input, overrides, force).map_err(Error::Parse)?;
         let ls = crate::parser::parse(&input).map_err(Error::Parse)?;
         Self::from_lines(ls, idx).map_err(Error::Evaluate)
     }
 
+    /// The number of the line, counting from 1, that each of the instructions of the warrior
+    /// parsed from `input` is on, in the order they are loaded into the core.
+    pub fn source_lines(input: &str) -> Result<Vec<usize>, Error> {
+        // Replacing definitions leaves every other line where it was.
+        let input = replace_definitions(input).map_err(Error::Parse)?;
+        let ls = crate::parser::parse_located(&input).map_err(Error::Parse)?;
+
+        Ok(ls
+            .into_iter()
+            .filter(|(_, line)| matches!(line, Line::Instruction(_)))
+            .map(|(number, _)| number)
+            .collect())
+    }
+
+    /// The labels defined in the Redcode `input`, each with the position of the instruction it
+    /// labels in the warrior, ordered by position.
+    pub fn labels(input: &str) -> Result<Vec<(String, usize)>, Error> {
+        let input = replace_definitions(input).map_err(Error::Parse)?;
+        let ls = crate::parser::parse(&input).map_err(Error::Parse)?;
+        let (instructions, _, _) = lines_by_type(ls);
+        let definitions = get_label_definitions(&instructions).map_err(Error::Evaluate)?;
+
+        let mut labels: Vec<(String, usize)> = definitions
+            .into_iter()
+            .map(|(label, position)| (label.to_owned(), position as usize))
+            .collect();
+        labels.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+
+        Ok(labels)
+    }
+
     pub fn len(&self) -> usize {
         self.instructions.len()
     }
@@ -222,6 +293,45 @@ impl Warrior {
         self.instructions.is_empty()
     }
 
+    /// The warrior's instructions, in the order they are loaded into the core.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// The line the warrior starts executing from.
+    pub fn starts_at_line(&self) -> usize {
+        self.starts_at_line
+    }
+
+    /// Writes the warrior out as Redcode, which parses back into an identical warrior.
+    pub fn to_redcode(&self) -> String {
+        let mut redcode = String::from(";redcode-94\n");
+        let fields = [
+            ("name", &self.metadata.name),
+            ("author", &self.metadata.author),
+            ("version", &self.metadata.version),
+            ("date", &self.metadata.date),
+        ];
+        for (key, value) in fields.iter() {
+            if let Some(value) = value {
+                redcode.push_str(&format!(";{} {}\n", key, value));
+            }
+        }
+        if let Some(ref strategy) = self.metadata.strategy {
+            for line in strategy.lines() {
+                redcode.push_str(&format!(";strategy {}\n", line));
+            }
+        }
+
+        redcode.push_str(&format!("\n        ORG     {}\n", self.starts_at_line));
+        for instruction in &self.instructions {
+            redcode.push_str(&format!("        {}\n", instruction));
+        }
+        redcode.push_str("        END\n");
+
+        redcode
+    }
+
     fn from_lines(lines: Vec<Line>, idx: usize) -> Result<Warrior, EvaluateError> {
         let mut metadata = Metadata::new();
         let (instructions, org_statements, metadata_values) = lines_by_type(lines);
@@ -253,7 +363,7 @@ fn lines_by_type<'a>(
 ) -> (
     Vec<RawInstruction<'a>>,
     Vec<NumericExpr<'a>>,
-    Vec<MetadataValue>,
+    Vec<MetadataValue<'a>>,
 ) {
     let mut org_statements = Vec::new();
     let mut instructions = Vec::new();
@@ -324,7 +434,7 @@ mod test {
     fn evaluate_dwarf_metadata() {
         let dwarf_str = include_str!("../warriors/dwarf.red");
 
-        let warrior = Warrior::parse(&dwarf_str, 0).unwrap();
+        let warrior = Warrior::parse(dwarf_str, 0).unwrap();
 
         assert_eq!(warrior.metadata.name().unwrap(), "Dwarf");
         assert_eq!(warrior.metadata.author().unwrap(), "A. K. Dewdney");
@@ -333,13 +443,61 @@ mod test {
 
         let bad_dwarf_str = include_str!("../warriors/bad_dwarf.red");
 
-        Warrior::parse(&bad_dwarf_str, 0).unwrap_err();
+        Warrior::parse(bad_dwarf_str, 0).unwrap_err();
+    }
+
+    #[test]
+    fn finds_source_lines() {
+        let dwarf = include_str!("../warriors/dwarf.red");
+        assert_eq!(Warrior::source_lines(dwarf).unwrap(), vec![17, 18, 19, 20]);
+        assert_eq!(
+            Warrior::labels(dwarf).unwrap(),
+            vec![(String::from("target"), 0), (String::from("start"), 1)]
+        );
+    }
+
+    #[test]
+    fn redcode_round_trips() {
+        for source in &[
+            include_str!("../warriors/dwarf.red"),
+            include_str!("../warriors/armadillo.red"),
+            include_str!("../warriors/impgate.red"),
+        ] {
+            let warrior = Warrior::parse(source, 0).unwrap();
+            let reparsed = Warrior::parse(&warrior.to_redcode(), 0).unwrap();
+            assert_eq!(reparsed, warrior);
+        }
+    }
+
+    #[test]
+    fn parse_with_overrides() {
+        let dwarf_str = include_str!("../warriors/dwarf.red");
+        let mut overrides = HashMap::new();
+        overrides.insert(String::from("step"), String::from("3044"));
+
+        let warrior = Warrior::parse_with_overrides(dwarf_str, 0, &overrides, false).unwrap();
+        assert_eq!(format!("{}", warrior.instructions[1]), "ADD.AB #3044, $-1");
+
+        overrides.insert(String::from("step"), String::from("1+2"));
+        let warrior = Warrior::parse_with_overrides(
+            &dwarf_str.replace("#step,", "#step*2,"),
+            0,
+            &overrides,
+            false,
+        )
+        .unwrap();
+        assert_eq!(format!("{}", warrior.instructions[1]), "ADD.AB #6, $-1");
+
+        overrides.insert(String::from("unused"), String::from("1"));
+        Warrior::parse_with_overrides(dwarf_str, 0, &overrides, false).unwrap_err();
+        let warrior = Warrior::parse_with_overrides(dwarf_str, 0, &overrides, true).unwrap();
+        assert_eq!(warrior.len(), 4);
     }
 
     #[test]
     fn evaluate_dwarf_lines() {
         let dwarf_str = include_str!("../warriors/dwarf.red");
-        let warrior = Warrior::parse(&dwarf_str, 0).unwrap();
+        let warrior = Warrior::parse(dwarf_str, 0).unwrap();
 
         assert_eq!(warrior.instructions.len(), 4);
 